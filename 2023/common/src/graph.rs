@@ -0,0 +1,115 @@
+// Tarjan's strongly connected components of a graph given as adjacency lists. The
+// depth-first search keeps its own stack of (node, next edge) frames instead of
+// recursing, so long chains cannot overflow the call stack. Components come out in
+// reverse topological order: every component reachable from one is emitted before it.
+pub fn strongly_connected_components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn enter(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.low[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+        }
+
+        fn visit(&mut self, root: usize) {
+            self.enter(root);
+            let mut frames = vec![(root, 0usize)];
+
+            while let Some(&mut (node, ref mut edge)) = frames.last_mut() {
+                if let Some(&target) = self.edges[node].get(*edge) {
+                    *edge += 1;
+                    match self.index[target] {
+                        None => {
+                            self.enter(target);
+                            frames.push((target, 0));
+                        },
+                        Some(index) if self.on_stack[target] => self.low[node] = self.low[node].min(index),
+                        Some(_) => (),
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some(&(parent, _)) = frames.last() {
+                    self.low[parent] = self.low[parent].min(self.low[node]);
+                }
+
+                if Some(self.low[node]) == self.index[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = self.stack.pop() {
+                        self.on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let n = edges.len();
+    let mut tarjan = Tarjan{
+        edges,
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..n {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan.components
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn sorted(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for component in components.iter_mut() {
+            component.sort();
+        }
+        components
+    }
+
+    #[test]
+    fn test_components() {
+        // 0 <-> 1 -> 2 <-> 3 -> 4, with 4 looping on itself and 5 on its own.
+        let edges = vec![vec![1], vec![0, 2], vec![3], vec![2, 4], vec![4], vec![]];
+        assert_eq!(sorted(strongly_connected_components(&edges)), vec![vec![4], vec![2, 3], vec![0, 1], vec![5]]);
+    }
+
+    #[test]
+    fn test_long_chain() {
+        // A million nodes in one cycle would need a million nested calls if recursive.
+        let n = 1_000_000;
+        let edges: Vec<Vec<usize>> = (0..n).map(|node| vec![(node + 1) % n]).collect();
+        let components = strongly_connected_components(&edges);
+        assert_eq!(components.len(), 1);
+        assert_eq!(components[0].len(), n);
+
+        let edges: Vec<Vec<usize>> = (0..n).map(|node| if node + 1 < n { vec![node + 1] } else { vec![] }).collect();
+        let components = strongly_connected_components(&edges);
+        assert_eq!(components.len(), n);
+        assert_eq!(components[0], vec![n - 1]);
+    }
+}
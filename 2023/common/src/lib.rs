@@ -0,0 +1,4 @@
+// Helpers shared by more than one day.
+
+pub mod graph;
+pub mod random;
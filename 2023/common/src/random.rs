@@ -0,0 +1,17 @@
+// Small deterministic generator for randomized test fixtures: Knuth's MMIX linear
+// congruential generator, keeping the better-mixed high bits.
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Self{state: seed}
+    }
+
+    // Next value in 0..n.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) % n
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashSet;
+use std::process::ExitCode;
 
 fn part_a(stats: &Vec<u32>) -> u32 {
     stats.iter().map(|s| {
@@ -9,40 +10,118 @@ fn part_a(stats: &Vec<u32>) -> u32 {
     }).sum()
 }
 
-fn traverse(stats: &Vec<u32>, index: usize, cards: &mut u32) {
-    let num_wins = stats[index];
-    *cards += 1;
-    for new_idx in index + 1 .. index + 1 + num_wins as usize {
-        traverse(stats, new_idx, cards);
+// Copies held of each card once all wins are processed. Every copy of card i wins one
+// copy of each of the next stats[i] cards, so a single forward pass settles each card
+// before it hands out copies.
+fn copies(stats: &[u32]) -> Result<Vec<u64>, String> {
+    let mut copies = vec![1u64; stats.len()];
+    for (idx, &num_wins) in stats.iter().enumerate() {
+        let end = stats.len().min(idx + 1 + num_wins as usize);
+        for new_idx in idx + 1 .. end {
+            copies[new_idx] = copies[new_idx].checked_add(copies[idx])
+                .ok_or(format!("copies of card {} overflow", new_idx + 1))?;
+        }
     }
+    Ok(copies)
 }
 
-fn part_b(stats: &Vec<u32>) -> u32 {
-    let mut cards = 0u32;
-    for idx in 0usize..stats.len() {
-        traverse(&stats, idx, &mut cards);
-    }
-    cards
+fn part_b(stats: &[u32]) -> Result<u64, String> {
+    copies(stats)?.iter().try_fold(0u64, |cards, &n| cards.checked_add(n))
+        .ok_or("total card count overflows".to_string())
+}
+
+fn parse_numbers(s: &str) -> Result<HashSet<u32>, String> {
+    s.split_whitespace()
+        .map(|n| n.parse::<u32>().map_err(|_| format!("invalid number '{}'", n)))
+        .collect()
 }
 
-fn solution(input_file_path: &str) -> (u32, u32) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
+fn win_stats(input_file_path: &str) -> Result<Vec<u32>, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
 
-    let win_stats: Vec<u32> = data.lines().map(|l| {
-        let numbers = l.split_once(':').unwrap().1.split_once('|').unwrap();
-        let winning: HashSet<u32> = numbers.0.split_whitespace().map(|n| n.parse::<u32>().unwrap()).collect();
-        let scratched: HashSet<u32> = numbers.1.split_whitespace().map(|n| n.parse::<u32>().unwrap()).collect();
-        let matched: Vec<&u32> = winning.intersection(&scratched).collect();
+    data.lines().map(|l| {
+        let numbers = l.split_once(':')
+            .and_then(|(_, numbers)| numbers.split_once('|'))
+            .ok_or(format!("invalid card '{}'", l))?;
+        let winning = parse_numbers(numbers.0)?;
+        let scratched = parse_numbers(numbers.1)?;
 
-        matched.len() as u32
+        Ok(winning.intersection(&scratched).count() as u32)
     })
-    .collect();
+    .collect()
+}
+
+fn solution(input_file_path: &str) -> Result<(u32, u64), String> {
+    let win_stats = win_stats(input_file_path)?;
 
-    (part_a(&win_stats), part_b(&win_stats))
+    Ok((part_a(&win_stats), part_b(&win_stats)?))
 }
 
-fn main() {
-    println!("test:  {:?}", solution("day04/input/test.txt"));
-    println!("input: {:?}", solution("day04/input/input.txt"));
+// Usage: day04 --copies <input>
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, path] if flag == "--copies" => {
+            let stats = win_stats(path)?;
+            for (idx, (num_wins, copies)) in stats.iter().zip(copies(&stats)?).enumerate() {
+                println!("Card {}: {} matches, {} copies", idx + 1, num_wins, copies);
+            }
+            println!("total: {}", part_b(&stats)?);
+            Ok(())
+        },
+        _ => Err("expected --copies <input>".to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    for (label, input_file_path) in [("test: ", "day04/input/test.txt"), ("input:", "day04/input/input.txt")] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{} {:?}", label, result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((13, 30)));
+        assert_eq!(copies(&win_stats("input/test.txt").unwrap()), Ok(vec![1, 2, 4, 8, 14, 1]));
+    }
+
+    #[test]
+    fn test_large_counts() {
+        // Every card wins copies of all later ones, doubling the count each time.
+        let stats: Vec<u32> = (0..40).rev().collect();
+        assert_eq!(part_b(&stats), Ok((1u64 << 40) - 1));
+
+        let stats: Vec<u32> = (0..70).rev().collect();
+        assert!(part_b(&stats).is_err());
+
+        // Wins past the end of the table copy nothing.
+        assert_eq!(copies(&[5, 5]), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        assert!(win_stats("input/missing.txt").unwrap_err().starts_with("cannot read"));
+        assert!(solution("Cargo.toml").is_err());
+    }
 }
 
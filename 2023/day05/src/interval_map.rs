@@ -0,0 +1,200 @@
+// Piecewise translation of the naturals: sorted, non-overlapping, half-open source ranges,
+// each shifted by its own offset. Values outside every range map to themselves, so an
+// empty map is the identity.
+
+pub type Range = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Entry {
+    start: usize,
+    end: usize,
+    offset: i64,
+}
+
+fn shift(value: usize, offset: i64) -> usize {
+    (value as i128 + offset as i128) as usize
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntervalMap {
+    entries: Vec<Entry>,
+}
+
+impl IntervalMap {
+    pub fn new() -> Self {
+        Self{entries: Vec::new()}
+    }
+
+    // Builds a map from (source range, destination start) pairs, in any order.
+    pub fn from_ranges(ranges: impl IntoIterator<Item = (Range, usize)>) -> Result<Self, String> {
+        let mut entries: Vec<Entry> = ranges.into_iter()
+            .filter(|((start, end), _)| start < end)
+            .map(|((start, end), dst)| Entry{start, end, offset: dst as i64 - start as i64})
+            .collect();
+        entries.sort_by_key(|entry| entry.start);
+
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].end > pair[1].start) {
+            return Err(format!("overlapping ranges {}..{} and {}..{}",
+                               pair[0].start, pair[0].end, pair[1].start, pair[1].end));
+        }
+
+        Ok(Self::normalized(entries))
+    }
+
+    // Drops identity entries and merges neighbours with the same offset, so that equal
+    // mappings compare equal.
+    fn normalized(entries: Vec<Entry>) -> Self {
+        let mut merged: Vec<Entry> = Vec::with_capacity(entries.len());
+        for entry in entries.into_iter().filter(|entry| entry.offset != 0) {
+            match merged.last_mut() {
+                Some(last) if last.end == entry.start && last.offset == entry.offset => last.end = entry.end,
+                _ => merged.push(entry),
+            }
+        }
+        Self{entries: merged}
+    }
+
+    pub fn get(&self, value: usize) -> usize {
+        let i = self.entries.partition_point(|entry| entry.end <= value);
+        match self.entries.get(i) {
+            Some(entry) if entry.start <= value => shift(value, entry.offset),
+            _ => value,
+        }
+    }
+
+    // Splits a range into the pieces that share an offset, in source order.
+    fn pieces(&self, range: Range) -> Vec<(Range, i64)> {
+        let (mut start, end) = range;
+        let mut pieces = Vec::new();
+        let first = self.entries.partition_point(|entry| entry.end <= start);
+
+        for entry in self.entries[first..].iter().take_while(|entry| entry.start < end) {
+            if start < entry.start {
+                pieces.push(((start, entry.start), 0));
+                start = entry.start;
+            }
+            let piece_end = end.min(entry.end);
+            pieces.push(((start, piece_end), entry.offset));
+            start = piece_end;
+        }
+        if start < end {
+            pieces.push(((start, end), 0));
+        }
+
+        pieces
+    }
+
+    // Image of a range, as one range per piece of the source it was split into.
+    pub fn apply(&self, range: Range) -> Vec<Range> {
+        self.pieces(range).into_iter()
+            .map(|((start, end), offset)| (shift(start, offset), shift(end, offset)))
+            .collect()
+    }
+
+    // The same mapping with explicit identity entries for the gaps, covering 0..usize::MAX.
+    pub fn filled(&self) -> Vec<(Range, usize)> {
+        self.pieces((0, usize::MAX)).into_iter()
+            .map(|((start, end), offset)| ((start, end), shift(start, offset)))
+            .collect()
+    }
+
+    // The map that applies self first and then `next`.
+    pub fn compose(&self, next: &IntervalMap) -> IntervalMap {
+        let mut entries = Vec::new();
+        for ((start, end), dst) in self.filled() {
+            let offset = dst as i64 - start as i64;
+            for ((image_start, image_end), next_offset) in next.pieces((dst, shift(end, offset))) {
+                entries.push(Entry{
+                    start: shift(image_start, -offset),
+                    end: shift(image_end, -offset),
+                    offset: offset + next_offset,
+                });
+            }
+        }
+        Self::normalized(entries)
+    }
+
+    // Every source range whose image meets the given range, sorted and merged.
+    pub fn preimage(&self, range: Range) -> Vec<Range> {
+        let mut sources: Vec<Range> = self.filled().into_iter()
+            .filter_map(|((start, end), dst)| {
+                let low = range.0.max(dst);
+                let high = range.1.min(dst + (end - start));
+                (low < high).then(|| (start + (low - dst), start + (high - dst)))
+            })
+            .collect();
+        merge(&mut sources);
+        sources
+    }
+
+    // The inverse mapping, or None if two sources share a destination.
+    pub fn invert(&self) -> Option<IntervalMap> {
+        let mut entries: Vec<Entry> = self.filled().into_iter()
+            .map(|((start, end), dst)| Entry{start: dst, end: dst + (end - start), offset: start as i64 - dst as i64})
+            .collect();
+        entries.sort_by_key(|entry| entry.start);
+
+        if entries.windows(2).any(|pair| pair[0].end > pair[1].start) {
+            return None;
+        }
+        Some(Self::normalized(entries))
+    }
+}
+
+// Sorts ranges and joins the ones that overlap or touch.
+pub fn merge(ranges: &mut Vec<Range>) {
+    ranges.sort();
+    let mut merged: Vec<Range> = Vec::with_capacity(ranges.len());
+    for &(start, end) in ranges.iter() {
+        match merged.last_mut() {
+            Some(last) if last.1 >= start => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    *ranges = merged;
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_apply_and_get() {
+        let map = IntervalMap::from_ranges([((98, 100), 50), ((50, 98), 52)]).unwrap();
+
+        assert_eq!(map.get(49), 49);
+        assert_eq!(map.get(50), 52);
+        assert_eq!(map.get(99), 51);
+        assert_eq!(map.get(100), 100);
+        assert_eq!(map.apply((40, 110)), vec![(40, 50), (52, 100), (50, 52), (100, 110)]);
+        assert_eq!(map.filled()[..2], [((0, 50), 0), ((50, 98), 52)]);
+        assert!(IntervalMap::from_ranges([((0, 10), 5), ((9, 12), 0)]).is_err());
+    }
+
+    #[test]
+    fn test_compose_and_invert() {
+        let first = IntervalMap::from_ranges([((0, 10), 100), ((100, 110), 0)]).unwrap();
+        let second = IntervalMap::from_ranges([((5, 105), 1000), ((1000, 1100), 5)]).unwrap();
+        let composed = first.compose(&second);
+
+        for value in 0..200 {
+            assert_eq!(composed.get(value), second.get(first.get(value)), "{}", value);
+        }
+        assert_eq!(first.compose(&first), IntervalMap::new());
+        assert_eq!(first.invert(), Some(first.clone()));
+
+        let inverse = composed.invert().unwrap();
+        for value in 0..200 {
+            assert_eq!(inverse.get(composed.get(value)), value);
+        }
+
+        let mut inverted = inverse.apply((0, 1200));
+        merge(&mut inverted);
+        assert_eq!(composed.preimage((0, 1200)), inverted);
+
+        let collapsing = IntervalMap::from_ranges([((0, 10), 20)]).unwrap();
+        assert_eq!(collapsing.invert(), None);
+        assert_eq!(collapsing.preimage((25, 27)), vec![(5, 7), (25, 27)]);
+    }
+}
@@ -1,102 +1,226 @@
-use std::collections::HashMap;
-
-type Range = (usize, usize);
-type Mapper = HashMap<Range, usize>;
-
-fn map_range(range: &Range, mapper: &Mapper) -> Vec<Range> {
-    let mut ranges_out = Vec::<Range>::new();
-    let (mut range_min, range_max) = *range;
-    let mut relevant: Vec<&Range> = mapper.keys().filter(|r| r.0 < range_max && r.1 > range_min).collect();
-    relevant.sort_by(|&(a_min, _), &(b_min, _)| a_min.cmp(b_min));
-
-    for &map_range in relevant {
-        let (map_range_min, map_range_max) = map_range;
-        let dst_range_min = *mapper.get(&map_range).unwrap();
-        let discriminator = (usize::min(range_min, map_range_min) == range_min,
-                             usize::min(range_max, map_range_max) == range_max);
-        match discriminator {
-            (true,  true ) => {
-                // range overlaping map_range from below
-                if range_min != map_range_min {
-                    ranges_out.push((range_min, map_range_min));
-                }
-                ranges_out.push((dst_range_min, dst_range_min + range_max - map_range_min));
-                range_min = range_max;
-                break;
-            }
+mod interval_map;
 
-            (false, false) => {
-                // range overlaping map_range from above
-                ranges_out.push((dst_range_min + range_min - map_range_min, dst_range_min + map_range_max - map_range_min));
-                range_min = map_range_max;
-            }
+use std::process::ExitCode;
 
-            (false, true)  => {
-                // range fully contained in map_range
-                ranges_out.push((dst_range_min + range_min - map_range_min, dst_range_min + range_max - map_range_min));
-                range_min = range_max;
-                break;
-            }
+use interval_map::{merge, IntervalMap, Range};
 
-            (true,  false) => {
-                // map_range fully contained in range
-                ranges_out.push((range_min, map_range_min));
-                ranges_out.push((dst_range_min, dst_range_min + map_range_max - map_range_min));
-                range_min = map_range_max;
-            }
+// One "<source>-to-<destination> map:" section of the almanac.
+struct Stage {
+    source: String,
+    destination: String,
+    map: IntervalMap,
+}
+
+struct Almanac {
+    seeds: Vec<usize>,
+    stages: Vec<Stage>,
+}
+
+fn parse_number(s: &str) -> Result<usize, String> {
+    s.parse::<usize>().map_err(|_| format!("invalid number '{}'", s))
+}
+
+impl Almanac {
+    fn parse(data: &str) -> Result<Self, String> {
+        let mut sections = data.split("\n\n");
+
+        let seeds: Vec<usize> = sections
+            .next().unwrap()
+            .split_once(':').ok_or("missing seeds line")?.1
+            .split_whitespace()
+            .map(parse_number)
+            .collect::<Result<_, _>>()?;
+        if seeds.is_empty() {
+            return Err("no seeds".to_string());
+        }
+        if !seeds.len().is_multiple_of(2) {
+            return Err(format!("{} seed numbers do not pair up into ranges", seeds.len()));
         }
+        if let Some(pair) = seeds.chunks(2).find(|pair| pair[1] == 0 || pair[0].checked_add(pair[1]).is_none()) {
+            return Err(format!("invalid seed range {} {}", pair[0], pair[1]));
+        }
+
+        let stages: Vec<Stage> = sections.filter(|s| !s.trim().is_empty()).map(|s| {
+            let mut lines = s.lines();
+            let header = lines.next().unwrap();
+            let (source, destination) = header.strip_suffix(" map:")
+                .and_then(|name| name.split_once("-to-"))
+                .ok_or(format!("invalid section header '{}'", header))?;
+
+            let ranges = lines.map(|l| {
+                let range_def: Vec<usize> = l.split_whitespace().map(parse_number).collect::<Result<_, _>>()?;
+                match range_def[..] {
+                    [dst, src, nelems] => Ok(((src, src + nelems), dst)),
+                    _ => Err(format!("{}: invalid range '{}'", header, l)),
+                }
+            }).collect::<Result<Vec<_>, String>>()?;
+            let map = IntervalMap::from_ranges(ranges).map_err(|e| format!("{}: {}", header, e))?;
+
+            Ok(Stage{source: source.to_string(), destination: destination.to_string(), map})
+        }).collect::<Result<_, String>>()?;
+
+        if let Some(pair) = stages.windows(2).find(|pair| pair[0].destination != pair[1].source) {
+            return Err(format!("'{}' map is followed by '{}' map", pair[0].destination, pair[1].source));
+        }
+
+        Ok(Self{seeds, stages})
     }
 
-    if range_min != range_max {
-        ranges_out.push((range_min, range_max));
+    // parse guarantees at least one pair and no empty or overflowing ranges.
+    fn seed_ranges(&self) -> Vec<Range> {
+        self.seeds.chunks(2).map(|c| (c[0], c[0] + c[1])).collect()
     }
 
-    ranges_out
-}
+    fn composed(&self) -> IntervalMap {
+        self.stages.iter().fold(IntervalMap::new(), |composed, stage| composed.compose(&stage.map))
+    }
 
-fn find_min_mapping(ranges: &Vec<Range>, mappers: &Vec<Mapper>) -> usize {
-    let mut ranges_in = ranges.clone();
-    let mut ranges_out = Vec::<Range>::new();
+    // The value a seed takes at every stage, starting with the seed itself.
+    fn lineage(&self, seed: usize) -> Vec<(&str, usize)> {
+        let mut value = seed;
+        let mut lineage = vec![(self.stages.first().map_or("seed", |stage| stage.source.as_str()), seed)];
+        for stage in self.stages.iter() {
+            value = stage.map.get(value);
+            lineage.push((stage.destination.as_str(), value));
+        }
+        lineage
+    }
 
-    for mapper in mappers {
-        for range in ranges_in {
-            ranges_out.append(&mut map_range(&range, mapper));
+    // All seed ranges that end up in the given location range.
+    fn reverse(&self, locations: Range) -> Vec<Range> {
+        let composed = self.composed();
+        match composed.invert() {
+            Some(inverse) => {
+                let mut seeds = inverse.apply(locations);
+                merge(&mut seeds);
+                seeds
+            },
+            None => composed.preimage(locations),
         }
-        ranges_in = ranges_out;
-        ranges_out = Vec::new();
     }
+}
 
-    ranges_in.iter().map(|rng| rng.0).min().unwrap()
+fn find_min_mapping(ranges: &[Range], mapper: &IntervalMap) -> usize {
+    ranges.iter()
+        .flat_map(|&range| mapper.apply(range))
+        .map(|rng| rng.0)
+        .min().unwrap()
 }
 
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut sections = data.split("\n\n");
+fn load(input_file_path: &str) -> Result<Almanac, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    Almanac::parse(&data)
+}
 
-    let raw_seeds: Vec<usize> = sections
-        .next().unwrap()
-        .split_once(':').unwrap().1
-        .split_whitespace()
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect();
+fn solution(input_file_path: &str) -> Result<(usize, usize), String> {
+    let almanac = load(input_file_path)?;
+    let seed_to_location = almanac.composed();
+
+    let min_point = almanac.seeds.iter().map(|&seed| seed_to_location.get(seed)).min().unwrap();
 
-    let mappers: Vec<Mapper> = sections.map(|s| {
-        s.lines().skip(1).map(|l| {
-            let range_def: Vec<usize> = l.split_whitespace().map(|nstr| nstr.parse::<usize>().unwrap()).collect();
-            let (dst, src, nelems) = (range_def[0], range_def[1], range_def[2]);
-            ((src, src + nelems), dst)
-        }).collect::<Mapper>()
-    }).collect();
+    Ok((min_point, find_min_mapping(&almanac.seed_ranges(), &seed_to_location)))
+}
 
+fn print_lineage(almanac: &Almanac, seed: usize) {
+    let stages: Vec<String> = almanac.lineage(seed).iter()
+        .map(|(name, value)| format!("{} {}", name, value))
+        .collect();
+    println!("{}", stages.join(" -> "));
+}
 
-    let point_seeds: Vec<_> = raw_seeds.iter().map(|&s| (s, s + 1)).collect();
-    let range_seeds: Vec<_> = raw_seeds.chunks(2).map(|c| (c[0], c[0] + c[1])).collect();
+// Overlaps between two lists of ranges.
+fn intersect(a: &[Range], b: &[Range]) -> Vec<Range> {
+    a.iter()
+        .flat_map(|&(a_min, a_max)| b.iter().map(move |&(b_min, b_max)| (a_min.max(b_min), a_max.min(b_max))))
+        .filter(|(min, max)| min < max)
+        .collect()
+}
 
-    (find_min_mapping(&point_seeds, &mappers), find_min_mapping(&range_seeds, &mappers))
+// Usage: day05 --lineage <seed> <input>
+//        day05 --reverse <location> <input>
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, seed, path] if flag == "--lineage" => {
+            let seed = parse_number(seed)?;
+            print_lineage(&load(path)?, seed);
+            Ok(())
+        },
+        [flag, location, path] if flag == "--reverse" => {
+            let location = parse_number(location)?;
+            let almanac = load(path)?;
+            let seeds = almanac.reverse((location, location + 1));
+            println!("seeds mapping to location {}: {:?}", location, seeds);
+
+            let mut almanac_seeds = almanac.seed_ranges();
+            merge(&mut almanac_seeds);
+            for (min, max) in intersect(&seeds, &almanac_seeds) {
+                for seed in min..max {
+                    print_lineage(&almanac, seed);
+                }
+            }
+            Ok(())
+        },
+        _ => Err("expected --lineage <seed> <input> or --reverse <location> <input>".to_string()),
+    }
 }
 
-fn main() {
-    println!("test:  {:?}", solution("day05/input/test.txt"));
-    println!("input: {:?}", solution("day05/input/input.txt"));
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    for (label, input_file_path) in [("test: ", "day05/input/test.txt"), ("input:", "day05/input/input.txt")] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{} {:?}", label, result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
 }
 
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((35, 46)));
+    }
+
+    #[test]
+    fn test_lineage_and_reverse() {
+        let almanac = load("input/test.txt").unwrap();
+
+        assert_eq!(almanac.lineage(79), vec![
+            ("seed", 79), ("soil", 81), ("fertilizer", 81), ("water", 81),
+            ("light", 74), ("temperature", 78), ("humidity", 78), ("location", 82),
+        ]);
+        assert_eq!(almanac.lineage(82).last(), Some(&("location", 46)));
+
+        let seeds = almanac.reverse((46, 47));
+        assert!(seeds.contains(&(82, 83)));
+        assert_eq!(intersect(&seeds, &almanac.seed_ranges()), vec![(82, 83)]);
+        for &(min, _) in seeds.iter() {
+            assert_eq!(almanac.composed().get(min), 46);
+        }
+    }
+
+    #[test]
+    fn test_malformed_seeds() {
+        let maps = "\n\nseed-to-soil map:\n50 98 2\n";
+        assert!(Almanac::parse(&format!("seeds: 79 14 55 13{}", maps)).is_ok());
+        assert_eq!(Almanac::parse(&format!("seeds:{}", maps)).err(), Some("no seeds".to_string()));
+        assert!(Almanac::parse(&format!("seeds: 79 14 55{}", maps)).is_err());
+        assert!(Almanac::parse(&format!("seeds: 79 0{}", maps)).is_err());
+        assert!(Almanac::parse(&format!("seeds: {} 2{}", usize::MAX, maps)).is_err());
+    }
+}
@@ -1,68 +1,152 @@
 use std::iter::zip;
+use std::process::ExitCode;
+
+// Number of integers x with x^2 - b*x + c < 0, without leaving the integers.
+//
+// Completing the square, x^2 - b*x + c < 0  <=>  (2x - b)^2 < b^2 - 4c = D, so this
+// counts the y = 2x - b with y^2 < D and y of the same parity as b. With m the largest
+// integer whose square is below D, those are -m..=m, stepping by 2 from whichever end
+// has b's parity. D always fits in a u128, and the count never exceeds b.
+fn count_negative(b: u64, c: u64) -> u64 {
+    let discriminant = match (b as u128 * b as u128).checked_sub(4 * c as u128) {
+        Some(discriminant) if discriminant > 0 => discriminant,
+        _ => return 0,
+    };
+
+    let m = (discriminant - 1).isqrt() as u64;
+    if m % 2 == b % 2 { m + 1 } else { m }
+}
 
-fn solve(time: usize, distance: usize) -> usize {
-    /* s - distance
-     * t - time
-     * d - delay
-     *
-     * s(t) = max(0, dt - d^2)
-     *
-     * To 'beat' the record for given s0, t0
-     *
-     * s(t0) > s0
-     *
-     * As a function of d:
-     *
-     * -d^2 + t0 * d - s0 > 0
-     *
-     * To solve, find all integer solutions of this quadratic inequality.
-     */
-
-    let mut num_strategies = 0usize;
-
-    let delta = time.pow(2) as f64 - 4.0 * distance as f64;
-
-    if delta > 0.0 {
-        let d0 = (-1.0f64 * time as f64 - delta.sqrt()) / -2.0;
-        let d1 = (-1.0f64 * time as f64 + delta.sqrt()) / -2.0;
-
-        let mut beg = f64::min(d0, d1);
-        let mut end = f64::max(d0, d1);
-
-        if beg.fract() == 0.0f64 {
-            beg += 1.0f64;
-        }
+/* s - distance
+ * t - time
+ * d - delay
+ *
+ * s(t) = max(0, dt - d^2)
+ *
+ * To 'beat' the record for given s0, t0
+ *
+ * s(t0) > s0
+ *
+ * As a function of d:
+ *
+ * d^2 - t0 * d + s0 < 0
+ *
+ * To solve, count all integer solutions of this quadratic inequality.
+ */
+fn solve(time: u64, distance: u64) -> u64 {
+    count_negative(time, distance)
+}
 
-        if end.fract() == 0.0f64 {
-            end -= 1.0f64;
-        }
+fn parse_number(s: &str) -> Result<u64, String> {
+    s.parse::<u64>().map_err(|_| format!("invalid or too large number '{}'", s))
+}
 
-        num_strategies = f64::floor(end) as usize - f64::ceil(beg) as usize + 1;
+fn solve_races(data: &str) -> Result<(u64, u64), String> {
+    let lines: Vec<Vec<&str>> = data.lines().map(|l| {
+        l.split_once(':').map(|(_, values)| values.split_whitespace().collect()).ok_or(format!("invalid line '{}'", l))
+    }).collect::<Result<_, String>>()?;
+    let [times, distances] = &lines[..] else {
+        return Err("expected a Time and a Distance line".to_string());
+    };
+
+    let mut product = 1u64;
+    for (time, distance) in zip(times, distances) {
+        product = product.checked_mul(solve(parse_number(time)?, parse_number(distance)?))
+            .ok_or("product of winning counts overflows")?;
     }
 
-    num_strategies
+    // Kerning joins the digits as written, so the joined numbers must still fit a u64.
+    let total_time = parse_number(&times.concat()).map_err(|e| format!("kerned time: {}", e))?;
+    let total_distance = parse_number(&distances.concat()).map_err(|e| format!("kerned distance: {}", e))?;
+
+    Ok((product, solve(total_time, total_distance)))
 }
 
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
+fn solution(input_file_path: &str) -> Result<(u64, u64), String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    solve_races(&data)
+}
 
-    let values: Vec<Vec<usize>> = data.lines().map(|l| {
-        l.split_once(":").unwrap().1.trim().split_whitespace().map(|s| s.parse::<usize>().unwrap()).collect()
-    }).collect();
+fn main() -> ExitCode {
+    for (label, input_file_path) in [("test: ", "day06/input/test.txt"), ("input:", "day06/input/input.txt")] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{} {:?}", label, result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
+}
 
-    let mut product = 1usize;
-    for (&time, &distance) in zip(&values[0], &values[1]){
-       product *= solve(time, distance);
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    // The previous floating point version, kept to show where it breaks down.
+    fn solve_f64(time: u64, distance: u64) -> u64 {
+        let delta = time.pow(2) as f64 - 4.0 * distance as f64;
+        if delta <= 0.0 {
+            return 0;
+        }
+
+        let mut beg = (time as f64 - delta.sqrt()) / 2.0;
+        let mut end = (time as f64 + delta.sqrt()) / 2.0;
+        if beg.fract() == 0.0 {
+            beg += 1.0;
+        }
+        if end.fract() == 0.0 {
+            end -= 1.0;
+        }
+        f64::floor(end) as u64 - f64::ceil(beg) as u64 + 1
     }
 
-    let total_time     = values[0].iter().fold(0usize, |acc, elem| acc * 10usize.pow(elem.ilog10() + 1) + elem);
-    let total_distance = values[1].iter().fold(0usize, |acc, elem| acc * 10usize.pow(elem.ilog10() + 1) + elem);
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((288, 71503)));
+    }
 
-    (product, solve(total_time, total_distance))
-}
+    #[test]
+    fn test_small_races_match_brute_force() {
+        for time in 0..60 {
+            for distance in 0..400 {
+                let expected = (0..=time).filter(|&d| d * (time - d) > distance).count() as u64;
+                assert_eq!(solve(time, distance), expected, "{} {}", time, distance);
+            }
+        }
+        assert_eq!(count_negative(1, 0), 0);
+        assert_eq!(count_negative(3, 1), 2);
+    }
 
-fn main() {
-    println!("test:  {:?}", solution("day06/input/test.txt"));
-    println!("input: {:?}", solution("day06/input/input.txt"));
-}
+    #[test]
+    fn test_huge_races() {
+        // Roots at exactly p and q, so the winning delays are p+1..q.
+        let (p, q) = (2_000_000_011u64, 2_000_000_033u64);
+        assert_eq!(solve(p + q, p * q), q - p - 1);
+        assert_ne!(solve_f64(p + q, p * q), q - p - 1);
+
+        // One past the record at the best delay, so only that delay wins.
+        let time = 4_000_000_000u64;
+        let best = (time / 2) * (time / 2);
+        assert_eq!(solve(time, best - 1), 1);
+        assert_eq!(solve(time, best), 0);
+        assert_ne!(solve_f64(time, best - 1), 1);
+
+        // Past i64::MAX: roots at 1 and u64::MAX - 1.
+        assert_eq!(solve(u64::MAX, u64::MAX - 1), u64::MAX - 3);
+    }
 
+    #[test]
+    fn test_huge_kerned_race() {
+        // Kerns to a time of 4_000_000_000 against a record one short of the best.
+        let data = "Time:      4000  000000\n\
+                    Distance:  3999999999  999999999\n";
+        assert_eq!(solve_races(data), Ok((0, 1)));
+
+        let data = "Time:      10000000000000000000  0\n\
+                    Distance:  1  0\n";
+        assert!(solve_races(data).unwrap_err().starts_with("kerned time"));
+    }
+}
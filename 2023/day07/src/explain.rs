@@ -0,0 +1,107 @@
+use crate::ruleset::Ruleset;
+
+// One hand's way through the scoring, for checking totals by eye.
+pub struct Row {
+    pub rank: usize,
+    pub hand: String,
+    pub bid: u32,
+    pub plain: String,
+    pub wild: String,
+    pub substitution: String,
+    pub winnings: usize,
+}
+
+pub fn explain(rules: &Ruleset, hands: &[(Vec<char>, u32)]) -> Vec<Row> {
+    rules.rank(hands).into_iter().enumerate().map(|(i, (hand, bid, classification))| {
+        let substitution: Vec<String> = hand.iter().zip(classification.resolved.iter())
+            .filter(|(card, resolved)| card != resolved)
+            .map(|(card, resolved)| format!("{}->{}", card, resolved))
+            .collect();
+        Row{
+            rank: i + 1,
+            hand: hand.iter().collect(),
+            bid,
+            plain: rules.category_name(rules.category_of(&hand)).to_string(),
+            wild: rules.category_name(classification.category).to_string(),
+            substitution: if substitution.is_empty() { "-".to_string() } else { substitution.join(" ") },
+            winnings: (i + 1) * bid as usize,
+        }
+    }).collect()
+}
+
+const HEADERS: [&str; 7] = ["rank", "hand", "bid", "type", "wildcard type", "substitution", "bid x rank"];
+
+fn fields(row: &Row) -> [String; 7] {
+    [
+        row.rank.to_string(),
+        row.hand.clone(),
+        row.bid.to_string(),
+        row.plain.clone(),
+        row.wild.clone(),
+        row.substitution.clone(),
+        row.winnings.to_string(),
+    ]
+}
+
+pub fn table(rows: &[Row]) -> String {
+    let lines: Vec<[String; 7]> = rows.iter().map(fields).collect();
+    let mut widths = HEADERS.map(str::len);
+    for line in lines.iter() {
+        for (width, field) in widths.iter_mut().zip(line.iter()) {
+            *width = (*width).max(field.len());
+        }
+    }
+
+    let format_line = |fields: &[String]| -> String {
+        fields.iter().zip(widths.iter())
+            .map(|(field, &width)| format!("{:<width$}", field, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string() + "\n"
+    };
+
+    let mut output = format_line(&HEADERS.map(str::to_string));
+    output += &format_line(&widths.map(|width| "-".repeat(width)));
+    for line in lines.iter() {
+        output += &format_line(line);
+    }
+    output + &format!("total: {}\n", rows.iter().map(|row| row.winnings).sum::<usize>())
+}
+
+pub fn csv(rows: &[Row]) -> String {
+    let quote = |field: &str| -> String {
+        if field.contains([',', '"']) { format!("\"{}\"", field.replace('"', "\"\"")) } else { field.to_string() }
+    };
+
+    let mut output = HEADERS.join(",") + "\n";
+    for row in rows {
+        output += &(fields(row).iter().map(|field| quote(field)).collect::<Vec<String>>().join(",") + "\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+    use crate::parse_hands;
+
+    #[test]
+    fn test_explain() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let rules = Ruleset::jokers();
+        let rows = explain(&rules, &parse_hands(&data, &rules).unwrap());
+
+        let last = rows.last().unwrap();
+        assert_eq!((last.rank, last.hand.as_str(), last.bid), (5, "KTJJT", 220));
+        assert_eq!((last.plain.as_str(), last.wild.as_str()), ("Two pair", "Four of a kind"));
+        assert_eq!((last.substitution.as_str(), last.winnings), ("J->T J->T", 1100));
+        assert_eq!(rows.iter().map(|row| row.winnings).sum::<usize>(), 5905);
+
+        let csv = csv(&rows);
+        assert_eq!(csv.lines().next(), Some("rank,hand,bid,type,wildcard type,substitution,bid x rank"));
+        assert_eq!(csv.lines().nth(1), Some("1,32T3K,765,One pair,One pair,-,765"));
+        assert!(table(&rows).ends_with("total: 5905\n"));
+    }
+}
@@ -1,114 +1,105 @@
-use std::collections::HashMap;
-use std::cmp::Ordering;
+mod explain;
+mod ruleset;
 
-#[derive(Debug, Eq, Ord, PartialEq, PartialOrd)]
-enum HandType {
-    HighCard = 0,
-    OnePair = 1,
-    TwoPair = 2,
-    ThreeOfAKind = 3,
-    FullHouse = 4,
-    FourOfAKind = 5,
-    FiveOfAKind = 6,
-}
-
-fn get_hand_type(hand: &([char; 5], u32), use_jokers: bool) -> HandType {
-    let mut counter: HashMap<char, u32> = HashMap::new();
-
-    for chr in hand.0 {
-        counter.entry(chr).and_modify(|c| *c += 1).or_insert(1);
-    }
+use std::process::ExitCode;
 
-    let mut num_jokers = 0u32;
-
-    if use_jokers {
-        match counter.remove(&'J') {
-            Some(5) => return HandType::FiveOfAKind,
-            Some(n) => num_jokers = n,
-            None => (),
-        }
-    }
+use ruleset::Ruleset;
 
-    let mut counts:Vec<(char, u32)> = counter.drain().collect();
-    counts.sort_by(|(_, a_val), (_, b_val)| b_val.cmp(a_val));
-
-    if use_jokers {
-        counts[0].1 += num_jokers;
-    }
+fn parse_hands(data: &str, rules: &Ruleset) -> Result<Vec<(Vec<char>, u32)>, String> {
+    data.lines().map(|line| {
+        let (cards, bid) = line.split_once(' ').ok_or(format!("invalid line '{}'", line))?;
+        let bid = bid.parse::<u32>().map_err(|_| format!("invalid bid '{}'", bid))?;
+        Ok((rules.parse_hand(cards)?, bid))
+    }).collect()
+}
 
-    match counts[0].1 {
-        5 => HandType::FiveOfAKind,
-        4 => HandType::FourOfAKind,
-        3 => match counts[1].1 {
-            2 => HandType::FullHouse,
-            _ => HandType::ThreeOfAKind,
-        },
-        2 => match counts[1].1 {
-            2 => HandType::TwoPair,
-            _ => HandType::OnePair,
-        },
-        _ => HandType::HighCard,
-    }
+fn load_hands(input_file_path: &str, rules: &Ruleset) -> Result<Vec<(Vec<char>, u32)>, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    parse_hands(&data, rules)
 }
 
-fn get_label_value(chr: &char, use_jokers: bool) -> u32 {
-    match chr {
-        '2' => 2,
-        '3' => 3,
-        '4' => 4,
-        '5' => 5,
-        '6' => 6,
-        '7' => 7,
-        '8' => 8,
-        '9' => 9,
-        'T' => 10,
-        'J' => if use_jokers { 1 } else { 11 },
-        'Q' => 12,
-        'K' => 13,
-        'A' => 14,
-        _ => panic!(),
-    }
+fn winnings(input_file_path: &str, rules: &Ruleset) -> Result<usize, String> {
+    Ok(rules.winnings(&load_hands(input_file_path, rules)?))
 }
 
-fn compare(a: &[char; 5], b: &[char;5], use_jokers: bool) -> Ordering {
-    let a_values: Vec<u32> = a.iter().map(|chr| get_label_value(chr, use_jokers)).collect();
-    let b_values: Vec<u32> = b.iter().map(|chr| get_label_value(chr, use_jokers)).collect();
+fn solution(input_file_path: &str) -> Result<(usize, usize), String> {
+    let part_a = winnings(input_file_path, &Ruleset::standard())?;
+    let part_b = winnings(input_file_path, &Ruleset::jokers())?;
 
-    a_values.cmp(&b_values)
+    Ok((part_a, part_b))
 }
 
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let hands: Vec<([char;5], u32)> = data.lines().map(|line| {
-        let tokens = line.split_once(' ').unwrap();
-        (tokens.0.chars().take(5).collect::<Vec<char>>().try_into().unwrap(), tokens.1.parse::<u32>().unwrap())
-    }).collect();
+fn run_explain(path: &str, rules: &Ruleset, as_csv: bool) -> Result<(), String> {
+    let rows = explain::explain(rules, &load_hands(path, rules)?);
+    print!("{}", if as_csv { explain::csv(&rows) } else { explain::table(&rows) });
+    Ok(())
+}
 
-    let mut hands_no_jokers: Vec<([char;5], u32, HandType)> = hands.iter().map(|hand| (hand.0, hand.1, get_hand_type(hand, false))).collect();
-    let mut hands_with_jokers: Vec<([char;5], u32, HandType)> = hands.iter().map(|hand| (hand.0, hand.1, get_hand_type(hand, true))).collect();
+// Usage: day07 --rules <config> <input>
+//        day07 --explain <input> [--rules <config>] [--csv]
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, path, rest @ ..] if flag == "--explain" => {
+            let mut rules = Ruleset::jokers();
+            let mut as_csv = false;
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match arg.as_str() {
+                    "--csv" => as_csv = true,
+                    "--rules" => rules = Ruleset::load(rest.next().ok_or("missing value for '--rules'")?)?,
+                    _ => return Err(format!("unknown argument '{}'", arg)),
+                }
+            }
+            run_explain(path, &rules, as_csv)
+        },
+        [flag, config, path] if flag == "--rules" => {
+            let rules = Ruleset::load(config)?;
+            let hands = load_hands(path, &rules)?;
+            let ranked = rules.rank(&hands);
+            let mut i = 0;
+            while i < ranked.len() {
+                let category = ranked[i].2.category;
+                let count = ranked[i..].iter().take_while(|hand| hand.2.category == category).count();
+                println!("{}: {}", rules.category_name(category), count);
+                i += count;
+            }
+            println!("winnings: {}", rules.winnings(&hands));
+            Ok(())
+        },
+        _ => Err("expected --rules <config> <input> or --explain <input> [--rules <config>] [--csv]".to_string()),
+    }
+}
 
-    hands_no_jokers.sort_by(|a, b| {
-        match a.2.cmp(&b.2) {
-            Ordering::Equal => compare(&a.0, &b.0, false),
-            ord => ord,
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
         }
-    });
+        return ExitCode::SUCCESS;
+    }
 
-    hands_with_jokers.sort_by(|a, b| {
-        match a.2.cmp(&b.2) {
-            Ordering::Equal => compare(&a.0, &b.0, true),
-            ord => ord,
+    for (label, input_file_path) in [("test: ", "day07/input/test.txt"), ("input:", "day07/input/input.txt")] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{} {:?}", label, result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
         }
-    });
+    }
+    ExitCode::SUCCESS
+}
 
-    let part_a = hands_no_jokers.iter().enumerate().fold(0usize, |acc, elem| acc + (elem.0 + 1) * elem.1.1 as usize);
-    let part_b = hands_with_jokers.iter().enumerate().fold(0usize, |acc, elem| acc + (elem.0 + 1) * elem.1.1 as usize);
+#[cfg(test)]
+mod unit_tests {
 
-    (part_a, part_b)
-}
+    use super::*;
 
-fn main() {
-    println!("test:  {:?}", solution("day07/input/test.txt"));
-    println!("input: {:?}", solution("day07/input/input.txt"));
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((6440, 5905)));
+        assert!(solution("input/missing.txt").unwrap_err().starts_with("cannot read"));
+    }
 }
-
@@ -0,0 +1,278 @@
+use std::cmp::Ordering;
+
+// How hands of the same category are ordered.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TieBreak {
+    // Card by card in the order they were dealt.
+    Positional,
+    // Card by card after sorting each hand strongest first.
+    Sorted,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Category {
+    pub name: String,
+    // Group sizes a hand needs, e.g. [3, 2] for a full house.
+    groups: Vec<u32>,
+}
+
+impl Category {
+    // counts must be sorted in descending order.
+    fn accepts(&self, counts: &[u32]) -> bool {
+        self.groups.len() <= counts.len() && self.groups.iter().zip(counts).all(|(need, have)| need <= have)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Ruleset {
+    // Card labels from weakest to strongest.
+    order: Vec<char>,
+    wildcards: Vec<char>,
+    hand_size: usize,
+    tie_break: TieBreak,
+    // From weakest to strongest.
+    categories: Vec<Category>,
+}
+
+// A hand's category, together with the cards its wildcards stood in for.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Classification {
+    pub category: usize,
+    pub resolved: Vec<char>,
+}
+
+impl Ruleset {
+    pub fn standard() -> Self {
+        Self::parse(include_str!("../rules/standard.txt")).unwrap()
+    }
+
+    pub fn jokers() -> Self {
+        Self::parse(include_str!("../rules/jokers.txt")).unwrap()
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let config = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+        Self::parse(&config).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    // Parses "key = value" lines; '#' starts a comment. Keys are order, wildcards,
+    // hand_size, tie_break (positional or sorted) and category, which is repeated once
+    // per category as "<name>: <group sizes>".
+    pub fn parse(config: &str) -> Result<Self, String> {
+        let mut order = None;
+        let mut wildcards = Vec::new();
+        let mut hand_size = None;
+        let mut tie_break = TieBreak::Positional;
+        let mut categories = Vec::new();
+
+        for (n, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or(format!("line {}: expected 'key = value'", n + 1))?;
+
+            match key {
+                "order" => order = Some(value.chars().filter(|c| !c.is_whitespace()).collect::<Vec<char>>()),
+                "wildcards" => wildcards = value.chars().filter(|c| !c.is_whitespace()).collect(),
+                "hand_size" => hand_size = Some(value.parse::<usize>().map_err(|_| format!("line {}: invalid hand_size '{}'", n + 1, value))?),
+                "tie_break" => tie_break = match value {
+                    "positional" => TieBreak::Positional,
+                    "sorted" => TieBreak::Sorted,
+                    _ => return Err(format!("line {}: unknown tie_break '{}'", n + 1, value)),
+                },
+                "category" => {
+                    let (name, groups) = value.rsplit_once(':').ok_or(format!("line {}: expected '<name>: <group sizes>'", n + 1))?;
+                    let groups = groups.split_whitespace()
+                        .map(|g| g.parse::<u32>().map_err(|_| format!("line {}: invalid group size '{}'", n + 1, g)))
+                        .collect::<Result<Vec<u32>, String>>()?;
+                    categories.push(Category{name: name.trim().to_string(), groups});
+                },
+                _ => return Err(format!("line {}: unknown key '{}'", n + 1, key)),
+            }
+        }
+
+        let ruleset = Self{
+            order: order.ok_or("missing order")?,
+            wildcards,
+            hand_size: hand_size.ok_or("missing hand_size")?,
+            tie_break,
+            categories,
+        };
+        ruleset.validate()?;
+        Ok(ruleset)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some((i, &label)) = self.order.iter().enumerate().find(|&(i, label)| self.order[..i].contains(label)) {
+            return Err(format!("card '{}' appears twice in order (position {})", label, i + 1));
+        }
+        if let Some(label) = self.wildcards.iter().find(|label| !self.order.contains(label)) {
+            return Err(format!("wildcard '{}' is not in order", label));
+        }
+        if self.order.iter().all(|label| self.wildcards.contains(label)) {
+            return Err("order needs at least one card that is not a wildcard".to_string());
+        }
+        if self.hand_size == 0 {
+            return Err("hand_size must be at least 1".to_string());
+        }
+        if let Some(category) = self.categories.iter().find(|c| c.groups.iter().sum::<u32>() as usize > self.hand_size || c.groups.contains(&0)) {
+            return Err(format!("category '{}' cannot be reached with {} cards", category.name, self.hand_size));
+        }
+        match self.categories.first() {
+            Some(weakest) if weakest.groups.iter().sum::<u32>() <= 1 => Ok(()),
+            Some(weakest) => Err(format!("weakest category '{}' must accept every hand", weakest.name)),
+            None => Err("no categories".to_string()),
+        }
+    }
+
+    pub fn category_name(&self, category: usize) -> &str {
+        &self.categories[category].name
+    }
+
+    pub fn parse_hand(&self, cards: &str) -> Result<Vec<char>, String> {
+        let hand: Vec<char> = cards.chars().collect();
+        if hand.len() != self.hand_size {
+            return Err(format!("hand '{}' does not have {} cards", cards, self.hand_size));
+        }
+        if let Some(label) = hand.iter().find(|label| !self.order.contains(label)) {
+            return Err(format!("hand '{}' has unknown card '{}'", cards, label));
+        }
+        Ok(hand)
+    }
+
+    fn value(&self, label: char) -> usize {
+        self.order.iter().position(|&c| c == label).unwrap()
+    }
+
+    // Category with wildcards counted as ordinary cards.
+    pub fn category_of(&self, hand: &[char]) -> usize {
+        let mut counts: Vec<u32> = Vec::new();
+        let mut labels: Vec<char> = Vec::new();
+        for &label in hand {
+            match labels.iter().position(|&l| l == label) {
+                Some(i) => counts[i] += 1,
+                None => {
+                    labels.push(label);
+                    counts.push(1);
+                },
+            }
+        }
+        counts.sort_by(|a, b| b.cmp(a));
+        self.categories.iter().rposition(|category| category.accepts(&counts)).unwrap()
+    }
+
+    // Tries every way of standing the wildcards in for other cards and keeps the one
+    // reaching the strongest category; among equals, the first found with the strongest
+    // stand-ins. Only cards already in the hand, plus one fresh card per wildcard, are
+    // worth trying.
+    pub fn classify(&self, hand: &[char]) -> Classification {
+        let wild: Vec<usize> = (0..hand.len()).filter(|&i| self.wildcards.contains(&hand[i])).collect();
+
+        let mut candidates: Vec<char> = hand.iter().copied().filter(|label| !self.wildcards.contains(label)).collect();
+        candidates.sort_by_key(|&label| std::cmp::Reverse(self.value(label)));
+        candidates.dedup();
+        let fresh: Vec<char> = self.order.iter().rev()
+            .filter(|label| !self.wildcards.contains(label) && !candidates.contains(label))
+            .take(wild.len())
+            .copied()
+            .collect();
+        candidates.extend(fresh);
+
+        if wild.is_empty() {
+            return Classification{category: self.category_of(hand), resolved: hand.to_vec()};
+        }
+
+        let mut best: Option<Classification> = None;
+        let mut choice = vec![0usize; wild.len()];
+        loop {
+            let mut resolved = hand.to_vec();
+            for (&i, &c) in wild.iter().zip(choice.iter()) {
+                resolved[i] = candidates[c];
+            }
+            let category = self.category_of(&resolved);
+            if best.as_ref().is_none_or(|best| category > best.category) {
+                best = Some(Classification{category, resolved});
+            }
+
+            // Next non-decreasing choice, so each multiset of stand-ins is tried once.
+            match (0..choice.len()).rev().find(|&i| choice[i] + 1 < candidates.len()) {
+                Some(i) => {
+                    let next = choice[i] + 1;
+                    choice[i..].fill(next);
+                },
+                None => break,
+            }
+        }
+
+        best.unwrap()
+    }
+
+    // Orders two hands of the same category.
+    pub fn tie_break(&self, a: &[char], b: &[char]) -> Ordering {
+        let values = |hand: &[char]| -> Vec<usize> {
+            let mut values: Vec<usize> = hand.iter().map(|&label| self.value(label)).collect();
+            if self.tie_break == TieBreak::Sorted {
+                values.sort_by(|a, b| b.cmp(a));
+            }
+            values
+        };
+        values(a).cmp(&values(b))
+    }
+
+    // Hands with their bids and classifications, weakest first.
+    pub fn rank(&self, hands: &[(Vec<char>, u32)]) -> Vec<(Vec<char>, u32, Classification)> {
+        let mut ranked: Vec<(Vec<char>, u32, Classification)> = hands.iter()
+            .map(|(hand, bid)| (hand.clone(), *bid, self.classify(hand)))
+            .collect();
+        ranked.sort_by(|a, b| a.2.category.cmp(&b.2.category).then_with(|| self.tie_break(&a.0, &b.0)));
+        ranked
+    }
+
+    pub fn winnings(&self, hands: &[(Vec<char>, u32)]) -> usize {
+        self.rank(hands).iter().enumerate().map(|(i, hand)| (i + 1) * hand.1 as usize).sum()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        let jokers = Ruleset::jokers();
+        let hand = jokers.parse_hand("KTJJT").unwrap();
+        let classification = jokers.classify(&hand);
+
+        assert_eq!(jokers.category_name(classification.category), "Four of a kind");
+        assert_eq!(classification.resolved, "KTTTT".chars().collect::<Vec<char>>());
+        assert_eq!(jokers.category_name(jokers.classify(&['J'; 5]).category), "Five of a kind");
+        assert_eq!(Ruleset::standard().category_name(Ruleset::standard().classify(&hand).category), "Two pair");
+        assert!(jokers.parse_hand("KTJJ").is_err());
+    }
+
+    #[test]
+    fn test_variant_rules() {
+        let config = "order = W*23456789TJQKA\n\
+                      wildcards = W*\n\
+                      hand_size = 6\n\
+                      tie_break = sorted\n\
+                      category = Nothing:\n\
+                      category = Pair: 2\n\
+                      category = Two triples: 3 3\n\
+                      category = Six of a kind: 6\n";
+        let rules = Ruleset::parse(config).unwrap();
+
+        let classification = rules.classify(&rules.parse_hand("AAKW*K").unwrap());
+        assert_eq!(rules.category_name(classification.category), "Two triples");
+        assert_eq!(classification.resolved, "AAKAKK".chars().collect::<Vec<char>>());
+        assert_eq!(rules.category_name(rules.classify(&rules.parse_hand("AAKW*2").unwrap()).category), "Pair");
+        assert_eq!(rules.tie_break(&['2', 'A', '3', '3', '3', '3'], &['A', '2', '3', '3', '3', '3']), Ordering::Equal);
+
+        assert!(Ruleset::parse(&config.replace("Nothing:", "Nothing: 2")).is_err());
+        assert!(Ruleset::parse(&config.replace("wildcards = W*", "wildcards = X")).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use common::graph::strongly_connected_components;
+
+use crate::cycles::{GhostCycle, Network};
+
+// Node names in sorted order and, per node, the indices of its left and right targets.
+struct Graph<'a> {
+    names: Vec<&'a str>,
+    edges: Vec<[usize; 2]>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(network: &Network<'a>) -> Result<Self, String> {
+        let mut names: Vec<&str> = network.keys().copied().collect();
+        names.sort();
+        let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, &name)| (name, i)).collect();
+
+        let edges = names.iter().map(|name| {
+            let (left, right) = network[name];
+            let target = |to: &str| index.get(to).copied().ok_or(format!("{} leads to undefined node {}", name, to));
+            Ok([target(left)?, target(right)?])
+        }).collect::<Result<_, String>>()?;
+
+        Ok(Self{names, edges})
+    }
+}
+
+pub struct Report<'a> {
+    // Strongly connected components of the node graph with either turn allowed,
+    // largest first.
+    pub components: Vec<Vec<&'a str>>,
+    // Z nodes each start node can stand on at some point when following the directions.
+    pub reachable_z: BTreeMap<&'a str, Vec<&'a str>>,
+    // How each start node's walk settles into a cycle.
+    pub cycles: BTreeMap<&'a str, GhostCycle>,
+    // Nodes no start node ever stands on.
+    pub unreachable: Vec<&'a str>,
+}
+
+// Start nodes walk (node, instruction) states, since which turn is taken depends on
+// the instruction index as well as the node.
+fn walk(graph: &Graph, directions: &[char], start: usize) -> BTreeSet<usize> {
+    let len = directions.len();
+    let mut seen = vec![false; graph.names.len() * len];
+    let mut stack = vec![(start, 0)];
+    let mut visited = BTreeSet::new();
+
+    while let Some((node, instruction)) = stack.pop() {
+        if std::mem::replace(&mut seen[node * len + instruction], true) {
+            continue;
+        }
+        visited.insert(node);
+        let turn = if directions[instruction] == 'L' { 0 } else { 1 };
+        stack.push((graph.edges[node][turn], (instruction + 1) % len));
+    }
+
+    visited
+}
+
+pub fn analyse<'a>(directions: &[char], network: &Network<'a>) -> Result<Report<'a>, String> {
+    let graph = Graph::new(network)?;
+
+    let neighbours: Vec<Vec<usize>> = graph.edges.iter().map(|edges| edges.to_vec()).collect();
+    let mut components: Vec<Vec<&str>> = strongly_connected_components(&neighbours).into_iter()
+        .map(|members| {
+            let mut names: Vec<&str> = members.into_iter().map(|node| graph.names[node]).collect();
+            names.sort();
+            names
+        })
+        .collect();
+    components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+
+    let mut reachable_z = BTreeMap::new();
+    let mut cycles = BTreeMap::new();
+    let mut reached = vec![false; graph.names.len()];
+    for (start, name) in graph.names.iter().enumerate().filter(|(_, name)| name.ends_with('A')) {
+        let visited = walk(&graph, directions, start);
+        for &node in visited.iter() {
+            reached[node] = true;
+        }
+        reachable_z.insert(*name, visited.into_iter().map(|node| graph.names[node]).filter(|name| name.ends_with('Z')).collect());
+        cycles.insert(*name, GhostCycle::new(directions, network, name));
+    }
+
+    let unreachable = graph.names.iter().zip(reached.iter())
+        .filter(|(_, &reached)| !reached)
+        .map(|(&name, _)| name)
+        .collect();
+
+    Ok(Report{components, reachable_z, cycles, unreachable})
+}
+
+// Graphviz rendering with start nodes in green, Z nodes in red and nodes no start node
+// reaches greyed out.
+pub fn dot(network: &Network, report: &Report) -> String {
+    let mut names: Vec<&str> = network.keys().copied().collect();
+    names.sort();
+
+    let mut output = String::from("digraph network {\n");
+    for name in names.iter() {
+        let style = if name.ends_with('A') {
+            "style=filled, fillcolor=palegreen"
+        } else if name.ends_with('Z') {
+            "style=filled, fillcolor=salmon"
+        } else if report.unreachable.contains(name) {
+            "color=grey, fontcolor=grey"
+        } else {
+            ""
+        };
+        output += &format!("    \"{}\" [{}];\n", name, style);
+    }
+    for name in names.iter() {
+        let (left, right) = network[name];
+        if left == right {
+            output += &format!("    \"{}\" -> \"{}\" [label=\"LR\"];\n", name, left);
+        } else {
+            output += &format!("    \"{}\" -> \"{}\" [label=\"L\"];\n", name, left);
+            output += &format!("    \"{}\" -> \"{}\" [label=\"R\"];\n", name, right);
+        }
+    }
+    output + "}\n"
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+    use crate::parse;
+
+    #[test]
+    fn test_analyse() {
+        let data = std::fs::read_to_string("input/test_c.txt").unwrap();
+        let (directions, network) = parse(&data);
+        let report = analyse(&directions, &network).unwrap();
+
+        assert_eq!(report.components[0], vec!["22B", "22C", "22Z"]);
+        assert!(report.components.contains(&vec!["XXX"]));
+        assert_eq!(report.reachable_z["11A"], vec!["11Z"]);
+        assert_eq!(report.reachable_z["22A"], vec!["22Z"]);
+        assert_eq!(report.unreachable, vec!["XXX"]);
+        assert_eq!(report.cycles["11A"], GhostCycle{prefix: 1, period: 2, prefix_hits: vec![], offsets: vec![1]});
+        assert_eq!(report.cycles["22A"].period, 6);
+
+        let dot = dot(&network, &report);
+        assert!(dot.contains("    \"11B\" -> \"11Z\" [label=\"R\"];\n"));
+        assert!(dot.contains("    \"XXX\" [color=grey, fontcolor=grey];\n"));
+    }
+}
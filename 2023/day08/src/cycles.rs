@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+
+pub type Network<'a> = HashMap<&'a str, (&'a str, &'a str)>;
+
+// Where a ghost stands is its node together with the next instruction to read, so its
+// walk is eventually periodic: after `prefix` steps it repeats every `period` steps.
+#[derive(Debug, Eq, PartialEq)]
+pub struct GhostCycle {
+    pub prefix: usize,
+    pub period: usize,
+    // Steps before the cycle that end on a Z node.
+    pub prefix_hits: Vec<usize>,
+    // Offsets from the start of the cycle, below period, that end on a Z node.
+    pub offsets: Vec<usize>,
+}
+
+impl GhostCycle {
+    pub fn new(directions: &[char], network: &Network, start: &str) -> Self {
+        let mut first_seen: HashMap<(&str, usize), usize> = HashMap::new();
+        let mut z_steps = Vec::new();
+        let mut node = start;
+
+        for step in 0.. {
+            let instruction = step % directions.len();
+            if let Some(&seen) = first_seen.get(&(node, instruction)) {
+                let (prefix_hits, cycle_hits): (Vec<usize>, Vec<usize>) = z_steps.into_iter().partition(|&s| s < seen);
+                return Self{
+                    prefix: seen,
+                    period: step - seen,
+                    prefix_hits,
+                    offsets: cycle_hits.into_iter().map(|s| s - seen).collect(),
+                };
+            }
+            first_seen.insert((node, instruction), step);
+            if node.ends_with('Z') {
+                z_steps.push(step);
+            }
+
+            node = match directions[instruction] {
+                'L' => network[node].0,
+                 _  => network[node].1,
+            };
+        }
+
+        unreachable!()
+    }
+
+    // Whether the ghost stands on a Z node after the given number of steps.
+    pub fn hits(&self, step: usize) -> bool {
+        if step < self.prefix {
+            self.prefix_hits.contains(&step)
+        } else {
+            self.offsets.contains(&((step - self.prefix) % self.period))
+        }
+    }
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+// Combines t = r1 (mod m1) and t = r2 (mod m2) into a single congruence modulo
+// lcm(m1, m2), or None if they have no common solution. Moduli need not be coprime.
+pub fn crt((r1, m1): (u128, u128), (r2, m2): (u128, u128)) -> Option<(u128, u128)> {
+    let (g, p, _) = extended_gcd(m1 as i128, m2 as i128);
+    let diff = r2 as i128 - r1 as i128;
+    if diff % g != 0 {
+        return None;
+    }
+
+    let lcm = m1 / g as u128 * m2;
+    let step = (m2 / g as u128) as i128;
+    // m1 * k = diff (mod m2), with p the inverse of m1 / g modulo m2 / g.
+    let k = ((diff / g) % step * (p % step)).rem_euclid(step);
+    Some(((r1 + m1 * k as u128) % lcm, lcm))
+}
+
+// Smallest step count, at least 1, after which every ghost stands on a Z node.
+pub fn first_common_hit(cycles: &[GhostCycle]) -> Option<u128> {
+    let longest = cycles.iter().max_by_key(|cycle| cycle.prefix)?;
+
+    // Before the longest prefix, only that ghost's own early hits are candidates.
+    if let Some(step) = longest.prefix_hits.iter()
+        .filter(|&&step| step >= 1)
+        .find(|&&step| cycles.iter().all(|cycle| cycle.hits(step))) {
+        return Some(*step as u128);
+    }
+
+    // From there on every ghost is periodic, so each offset is a congruence on the step.
+    let mut congruences: Vec<(u128, u128)> = vec![(0, 1)];
+    for cycle in cycles {
+        let mut merged: Vec<(u128, u128)> = congruences.iter()
+            .flat_map(|&congruence| cycle.offsets.iter().filter_map(move |&offset| {
+                let residue = ((cycle.prefix + offset) % cycle.period) as u128;
+                crt(congruence, (residue, cycle.period as u128))
+            }))
+            .collect();
+        merged.sort();
+        merged.dedup();
+        congruences = merged;
+    }
+
+    let lower = longest.prefix.max(1) as u128;
+    congruences.into_iter()
+        .map(|(residue, modulus)| residue + (lower.saturating_sub(residue)).div_ceil(modulus) * modulus)
+        .min()
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use common::random::Lcg;
+
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt((2, 3), (3, 5)), Some((8, 15)));
+        assert_eq!(crt((1, 4), (3, 6)), Some((9, 12)));
+        assert_eq!(crt((1, 4), (2, 6)), None);
+        assert_eq!(crt((0, 1), (5, 7)), Some((5, 7)));
+    }
+
+    fn brute_force(directions: &[char], network: &Network, limit: usize) -> Option<u128> {
+        let mut nodes: Vec<&str> = network.keys().filter(|k| k.ends_with('A')).copied().collect();
+        for step in 1..limit {
+            let turn = directions[(step - 1) % directions.len()];
+            for node in nodes.iter_mut() {
+                *node = if turn == 'L' { network[*node].0 } else { network[*node].1 };
+            }
+            if nodes.iter().all(|node| node.ends_with('Z')) {
+                return Some(step as u128);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let names: Vec<String> = (0..12).map(|i| format!("{}{}", i, ['A', 'B', 'Z'][i % 3])).collect();
+        let mut lcg = Lcg::new(12345);
+        let mut random = |n: usize| lcg.below(n as u64) as usize;
+
+        for _ in 0..200 {
+            let directions: Vec<char> = (0..1 + random(4)).map(|_| if random(2) == 0 { 'L' } else { 'R' }).collect();
+            let network: Network = names.iter()
+                .map(|name| (name.as_str(), (names[random(names.len())].as_str(), names[random(names.len())].as_str())))
+                .collect();
+
+            let cycles: Vec<GhostCycle> = network.keys().filter(|k| k.ends_with('A'))
+                .map(|ghost| GhostCycle::new(&directions, &network, ghost))
+                .collect();
+            let expected = brute_force(&directions, &network, 5_000);
+            let found = first_common_hit(&cycles).filter(|&step| step < 5_000);
+            assert_eq!(found, expected, "{:?} {:?}", directions, network);
+        }
+    }
+
+    #[test]
+    fn test_no_common_hit() {
+        let network: Network = HashMap::from([
+            ("1A", ("1Z", "1Z")), ("1Z", ("1A", "1A")),
+            ("2A", ("2B", "2B")), ("2B", ("2Z", "2Z")), ("2Z", ("2B", "2B")),
+        ]);
+        let cycles: Vec<GhostCycle> = ["1A", "2A"].iter().map(|g| GhostCycle::new(&['L'], &network, g)).collect();
+
+        assert_eq!(cycles[1], GhostCycle{prefix: 1, period: 2, prefix_hits: vec![], offsets: vec![1]});
+        assert_eq!(first_common_hit(&cycles), None);
+    }
+}
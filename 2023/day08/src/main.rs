@@ -1,70 +1,113 @@
-use std::collections::HashMap;
-use std::iter::zip;
-
-fn traverse(directions: &Vec<char>, lr_map: &HashMap<&str, (&str, &str)>, from_node: &str) -> usize {
-    let mut node = from_node;
-    for (index, turn) in directions.iter().cycle().enumerate() {
-        match turn {
-            'L' => node = lr_map[node].0,
-             _  => node = lr_map[node].1,
-        }
-        if node.ends_with('Z') {
-            return index + 1;
-        }
-    }
+mod analysis;
+mod cycles;
 
-    0
-}
+use std::process::ExitCode;
 
-fn gcd(a: &usize, b: &usize) -> usize {
-    assert!(*a != 0 && *b != 0);
-    let mut m = a.clone();
-    let mut n = b.clone();
-    while m != 0 {
-        if m < n {
-            std::mem::swap(&mut m, &mut n);
-        }
-        m %= n;
-    }
-
-    n
-}
+use cycles::{first_common_hit, GhostCycle, Network};
 
-fn lcm(a: &usize, b: &usize) -> usize {
-    a * b / gcd(a, b)
-}
-
-fn solution(input_file_path: &str) -> usize {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
+fn parse(data: &str) -> (Vec<char>, Network<'_>) {
     let mut lines = data.lines();
 
     let directions: Vec<char> = lines.next().unwrap().chars().collect();
 
     lines.next().unwrap();
 
-    let mut lr_map: HashMap<&str, (&str, &str)> = HashMap::new();
+    let mut lr_map: Network = Network::new();
     for line in lines {
         let (from, to) = line.split_once(" = ").unwrap();
         let (to_left, to_right) = to[1 .. to.len()-1].split_once(", ").unwrap();
         lr_map.insert(from, (to_left, to_right));
     }
 
-    let ghosts: Vec<&str> = lr_map.keys().filter(|k| k.ends_with('A')).map(|&k| k).collect();
-    let cycles: Vec<usize> = ghosts.iter().map(|&g| traverse(&directions, &lr_map, g)).collect();
+    (directions, lr_map)
+}
+
+fn solution(input_file_path: &str) -> Option<u128> {
+    let data = std::fs::read_to_string(input_file_path).unwrap();
+    let (directions, lr_map) = parse(&data);
+
+    let mut ghosts: Vec<&str> = lr_map.keys().filter(|k| k.ends_with('A')).copied().collect();
+    ghosts.sort();
+    let cycles: Vec<GhostCycle> = ghosts.iter().map(|&g| GhostCycle::new(&directions, &lr_map, g)).collect();
+
+    first_common_hit(&cycles)
+}
 
-    println!("{:?}", zip(&ghosts, &cycles).collect::<Vec<_>>());
+fn run_analyse(path: &str) -> Result<(), String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+    let (directions, network) = parse(&data);
+    let report = analysis::analyse(&directions, &network)?;
 
-    match ghosts.len() {
-        0 => 0,
-        1 => cycles[0],
-        _ => cycles.iter().fold(cycles[0], |acc, elem| lcm(&acc, &elem))
+    let cyclic: Vec<&Vec<&str>> = report.components.iter()
+        .filter(|members| members.len() > 1 || network[members[0]].0 == members[0] || network[members[0]].1 == members[0])
+        .collect();
+    println!("nodes: {}, strongly connected components: {}, cyclic: {}", network.len(), report.components.len(), cyclic.len());
+    for members in cyclic {
+        println!("  size {}: {}", members.len(), members.join(" "));
+    }
+    for (start, reachable) in report.reachable_z.iter() {
+        println!("{} reaches: {}", start, if reachable.is_empty() { "no Z node".to_string() } else { reachable.join(" ") });
+        let cycle = &report.cycles[start];
+        println!("  prefix {}, period {}, Z offsets {:?}", cycle.prefix, cycle.period, cycle.offsets);
+    }
+    println!("unreachable from any start: {} nodes", report.unreachable.len());
+    if !report.unreachable.is_empty() {
+        println!("  {}", report.unreachable.join(" "));
+    }
+    Ok(())
+}
+
+fn run_dot(path: &str, output_file_path: Option<&str>) -> Result<(), String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+    let (directions, network) = parse(&data);
+    let dot = analysis::dot(&network, &analysis::analyse(&directions, &network)?);
+
+    match output_file_path {
+        Some(output) => std::fs::write(output, dot).map_err(|e| format!("cannot write '{}': {}", output, e)),
+        None => {
+            print!("{}", dot);
+            Ok(())
+        },
+    }
+}
+
+// Usage: day08 --analyse <input>
+//        day08 --dot <input> [--output <file>]
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, path] if flag == "--analyse" => run_analyse(path),
+        [flag, path] if flag == "--dot" => run_dot(path, None),
+        [flag, path, output_flag, output] if flag == "--dot" && output_flag == "--output" => run_dot(path, Some(output)),
+        _ => Err("expected --analyse <input> or --dot <input> [--output <file>]".to_string()),
     }
 }
 
-fn main() {
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
     println!("test_a: {:?}", solution("day08/input/test_a.txt"));
     println!("test_b: {:?}", solution("day08/input/test_b.txt"));
     println!("test_c: {:?}", solution("day08/input/test_c.txt"));
     println!("input:  {:?}", solution("day08/input/input.txt"));
+    ExitCode::SUCCESS
 }
 
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test_a.txt"), Some(2));
+        assert_eq!(solution("input/test_b.txt"), Some(6));
+        assert_eq!(solution("input/test_c.txt"), Some(6));
+    }
+}
@@ -0,0 +1,233 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::str::FromStr;
+
+// Signed arbitrary-precision integer: a sign and little-endian base 2^32 magnitude
+// without trailing zero limbs. Zero is never negative, so derived equality is exact.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+fn trim(magnitude: &mut Vec<u32>) {
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let limb = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(limb as u32);
+        carry = limb >> 32;
+    }
+    sum.push(carry as u32);
+    trim(&mut sum);
+    sum
+}
+
+// a - b, for a at least b.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut value = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = (value < 0) as i64;
+        if value < 0 {
+            value += 1 << 32;
+        }
+        difference.push(value as u32);
+    }
+    trim(&mut difference);
+    difference
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self{negative: false, magnitude: Vec::new()}
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        trim(&mut magnitude);
+        Self{negative: negative && !magnitude.is_empty(), magnitude}
+    }
+
+    // Exact division by a small positive divisor; panics if there is a remainder.
+    pub fn div_exact(&self, divisor: u32) -> BigInt {
+        let (quotient, remainder) = self.div_rem_magnitude(divisor);
+        assert!(remainder == 0, "{} is not divisible by {}", self, divisor);
+        Self::from_parts(self.negative, quotient)
+    }
+
+    fn div_rem_magnitude(&self, divisor: u32) -> (Vec<u32>, u32) {
+        let mut quotient = vec![0u32; self.magnitude.len()];
+        let mut remainder = 0u64;
+        for (i, &limb) in self.magnitude.iter().enumerate().rev() {
+            let value = (remainder << 32) | limb as u64;
+            quotient[i] = (value / divisor as u64) as u32;
+            remainder = value % divisor as u64;
+        }
+        (quotient, remainder as u32)
+    }
+}
+
+impl From<i128> for BigInt {
+    fn from(value: i128) -> Self {
+        let mut rest = value.unsigned_abs();
+        let mut magnitude = Vec::new();
+        while rest != 0 {
+            magnitude.push(rest as u32);
+            rest >>= 32;
+        }
+        Self::from_parts(value < 0, magnitude)
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        BigInt::from(value as i128)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("invalid integer '{}'", s));
+        }
+
+        let ten = BigInt::from(10i64);
+        let value = digits.bytes().fold(BigInt::zero(), |value, digit| &(&value * &ten) + &BigInt::from((digit - b'0') as i64));
+        Ok(if negative { -&value } else { value })
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitudes(&self.magnitude, &other.magnitude));
+        }
+        match compare_magnitudes(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(other.negative, sub_magnitudes(&other.magnitude, &self.magnitude)),
+            _ => BigInt::from_parts(self.negative, sub_magnitudes(&self.magnitude, &other.magnitude)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        let mut product = vec![0u32; self.magnitude.len() + other.magnitude.len()];
+        for (i, &a) in self.magnitude.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.magnitude.iter().enumerate() {
+                let value = product[i + j] as u64 + a as u64 * b as u64 + carry;
+                product[i + j] = value as u32;
+                carry = value >> 32;
+            }
+            product[i + other.magnitude.len()] = carry as u32;
+        }
+        BigInt::from_parts(self.negative != other.negative, product)
+    }
+}
+
+impl std::iter::Sum for BigInt {
+    fn sum<I: Iterator<Item = BigInt>>(iter: I) -> Self {
+        iter.fold(BigInt::zero(), |sum, value| &sum + &value)
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u32 = 1_000_000_000;
+
+        let mut rest = BigInt::from_parts(false, self.magnitude.clone());
+        let mut chunks = Vec::new();
+        while !rest.is_zero() {
+            let (quotient, remainder) = rest.div_rem_magnitude(CHUNK);
+            chunks.push(remainder);
+            rest = BigInt::from_parts(false, quotient);
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:09}", chunk)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Debug for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("-170141183460469231731687303715884105728");
+        let b = big("99999999999999999999");
+
+        assert_eq!(a, BigInt::from(i128::MIN));
+        assert_eq!((&a - &b).to_string(), "-170141183460469231831687303715884105727");
+        assert_eq!((&b + &a).to_string(), "-170141183460469231631687303715884105729");
+        assert_eq!((&a * &b).to_string(), "-17014118346046923172998589188127941341068312696284115894272");
+        assert_eq!((&b - &b), BigInt::zero());
+        assert_eq!((&b * &BigInt::from(6i64)).div_exact(3).to_string(), "199999999999999999998");
+        assert_eq!(big("-0").to_string(), "0");
+        assert!("12a".parse::<BigInt>().is_err());
+    }
+}
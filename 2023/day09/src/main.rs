@@ -1,36 +1,75 @@
-fn interpolate(history: &Vec<i32>) -> (i32, i32) {
-    if history.iter().all(|&v| v == 0) {
-        return (0, 0);
-    }
+mod bigint;
+mod sequence;
 
-    assert!(history.len() > 1);
+use std::process::ExitCode;
 
-    let diffs: Vec<i32> = history.windows(2).map(|w| w[1] - w[0]).collect();
-    let interp = interpolate(&diffs);
+use bigint::BigInt;
+use sequence::Sequence;
 
-    (history.first().unwrap() - interp.0, history.last().unwrap() + interp.1)
+fn parse_log(input_file_path: &str) -> Result<Vec<Sequence>, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    data.lines().enumerate().map(|(n, line)| {
+        let history = line.split_whitespace().map(str::parse::<BigInt>).collect::<Result<Vec<BigInt>, String>>()?;
+        Sequence::fit(&history).map_err(|e| format!("line {}: {}", n + 1, e))
+    }).collect()
 }
 
-fn solution(input_file_path: &str) -> (i32, i32) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut log: Vec<Vec<i32>> = Vec::new();
-    for line in data.lines() {
-        log.push(
-            line.split_whitespace().map(|s| s.parse::<i32>().unwrap()).collect()
-        );
+fn solution(input_file_path: &str) -> Result<(BigInt, BigInt), String> {
+    let log = parse_log(input_file_path)?;
+
+    let one = BigInt::from(1i64);
+    Ok((log.iter().map(|s| s.previous(&one)).sum(), log.iter().map(|s| s.next(&one)).sum()))
+}
+
+// Usage: day09 --extrapolate <k> <input>
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, k, path] if flag == "--extrapolate" => {
+            let k: BigInt = k.parse().map_err(|_| format!("invalid --extrapolate '{}'", k))?;
+            let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+            for (n, line) in data.lines().enumerate() {
+                let history = line.split_whitespace().map(str::parse::<BigInt>).collect::<Result<Vec<BigInt>, String>>()?;
+                match Sequence::fit(&history) {
+                    Ok(sequence) => println!("line {}: degree {}, {} before: {}, {} after: {}",
+                                             n + 1, sequence.degree, k, sequence.previous(&k), k, sequence.next(&k)),
+                    Err(e) => println!("line {}: not polynomial, {}", n + 1, e),
+                }
+            }
+            Ok(())
+        },
+        _ => Err("expected --extrapolate <k> <input>".to_string()),
     }
+}
 
-    for value_history in &mut log {
-        let interp = interpolate(&value_history);
-        value_history.insert(0, interp.0);
-        value_history.push(interp.1);
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
     }
 
-    (log.iter().map(|h| h.first().unwrap()).sum(), log.iter().map(|h| h.last().unwrap()).sum())
+    for input_file_path in ["day09/input/test.txt", "day09/input/input.txt"] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
 }
 
-fn main() {
-    println!("{:?}", solution("day09/input/test.txt"));
-    println!("{:?}", solution("day09/input/input.txt"));
-}
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
 
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((BigInt::from(2i64), BigInt::from(114i64))));
+    }
+}
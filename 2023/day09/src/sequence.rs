@@ -0,0 +1,99 @@
+use crate::bigint::BigInt;
+
+// A history that some polynomial of the given degree passes through, stored as the
+// leading entries of its difference table so it can be evaluated at any index.
+#[derive(Debug)]
+pub struct Sequence {
+    pub degree: usize,
+    len: usize,
+    // differences[j] is the j-th forward difference at index 0.
+    differences: Vec<BigInt>,
+}
+
+impl Sequence {
+    // Differences the history until a row is all zeros. The history is only known to
+    // be polynomial if that happens while a row is still left to check, i.e. a degree d
+    // fit needs at least d + 2 values.
+    pub fn fit(history: &[BigInt]) -> Result<Self, String> {
+        let mut row = history.to_vec();
+        let mut differences = Vec::new();
+
+        while !row.is_empty() {
+            if row.iter().all(BigInt::is_zero) {
+                let degree = differences.len().saturating_sub(1);
+                return Ok(Self{degree, len: history.len(), differences});
+            }
+            differences.push(row[0].clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+
+        Err(format!("{} values are not enough to show a polynomial through them", history.len()))
+    }
+
+    // Value at an index counted from the first entry of the history, which may be
+    // negative or past its end: the Newton forward-difference series with generalized
+    // binomial coefficients C(n, j).
+    pub fn value_at(&self, n: &BigInt) -> BigInt {
+        let mut binomial = BigInt::from(1i64);
+        let mut value = BigInt::zero();
+
+        for (j, difference) in self.differences.iter().enumerate() {
+            if j > 0 {
+                binomial = (&binomial * &(n - &BigInt::from(j as i64 - 1))).div_exact(j as u32);
+            }
+            value = &value + &(&binomial * difference);
+        }
+
+        value
+    }
+
+    // The value k steps after the last entry.
+    pub fn next(&self, k: &BigInt) -> BigInt {
+        self.value_at(&(&BigInt::from(self.len as i128 - 1) + k))
+    }
+
+    // The value k steps before the first entry.
+    pub fn previous(&self, k: &BigInt) -> BigInt {
+        self.value_at(&-k)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn sequence(values: &[i64]) -> Result<Sequence, String> {
+        Sequence::fit(&values.iter().map(|&v| BigInt::from(v)).collect::<Vec<BigInt>>())
+    }
+
+    fn big(value: i128) -> BigInt {
+        BigInt::from(value)
+    }
+
+    #[test]
+    fn test_fit_and_extrapolate() {
+        let squares = sequence(&[1, 4, 9, 16]).unwrap();
+        assert_eq!(squares.degree, 2);
+        assert_eq!(squares.next(&big(1)), BigInt::from(25i64));
+        assert_eq!(squares.previous(&big(3)), BigInt::from(4i64));
+        assert_eq!(squares.next(&big(999_999_999_999_999_996)).to_string(), "1000000000000000000000000000000000000");
+
+        let cubes = sequence(&[-8, -1, 0, 1, 8]).unwrap();
+        assert_eq!(cubes.degree, 3);
+        assert_eq!(cubes.value_at(&big(-999_999_998)).to_string(), "-1000000000000000000000000000");
+
+        assert_eq!(sequence(&[0, 0]).unwrap().next(&big(5)), BigInt::zero());
+        assert_eq!(sequence(&[7, 7]).unwrap().degree, 0);
+        assert!(sequence(&[1, 2, 4, 8]).is_err());
+        assert!(sequence(&[5]).is_err());
+    }
+
+    #[test]
+    fn test_extrapolate_past_i128() {
+        let line = sequence(&[1, 3, 5]).unwrap();
+        assert_eq!(line.next(&big(i128::MAX)).to_string(), "340282366920938463463374607431768211459");
+        assert_eq!(line.previous(&big(i128::MAX)).to_string(), "-340282366920938463463374607431768211453");
+        assert_eq!(line.previous(&big(i128::MIN)).to_string(), "340282366920938463463374607431768211457");
+    }
+}
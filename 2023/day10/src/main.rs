@@ -1,33 +1,74 @@
 use std::collections::HashSet;
+use std::fmt;
+use std::process::ExitCode;
 
-fn get_neighbors(sketch: &Vec<Vec<char>>, row: usize, col: usize) -> (char, char, char, char) {
-    let num_rows = sketch.len();
-    let num_cols = sketch[0].len();
+mod render;
 
-    let n_char = match row {
-        0 => '.',
-        _ => sketch[row - 1][col],
-    };
+#[derive(Debug, Eq, PartialEq)]
+enum PipeError {
+    Io { message: String },
+    MissingStart,
+    NoLoop { start: (usize, usize) },
+    BrokenLoop { at: (usize, usize), tile: char },
+}
 
-    let e_char = match col {
-        n if n >= num_cols - 1 => '.',
-        _ => sketch[row][col + 1],
-    };
+impl fmt::Display for PipeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PipeError::Io { message } =>
+                write!(f, "cannot read the sketch: {}", message),
+            PipeError::MissingStart =>
+                write!(f, "no 'S' tile in the sketch"),
+            PipeError::NoLoop { start } =>
+                write!(f, "start tile at {:?} has fewer than two mutual connections", start),
+            PipeError::BrokenLoop { at, tile } =>
+                write!(f, "loop is broken at {:?} (tile '{}')", at, tile),
+        }
+    }
+}
 
-    let s_char = match row {
-        n if n >= num_rows - 1 => '.',
-        _ => sketch[row + 1][col],
-    };
+type Sketch = Vec<Vec<char>>;
 
-    let w_char = match col {
-        0 => '.',
-        _ => sketch[row][col - 1],
-    };
+const TILES: [char; 6] = ['|', '-', 'L', 'J', '7', 'F'];
+
+fn connections(tile: char) -> &'static [char] {
+    match tile {
+        '|' => &['N', 'S'],
+        '-' => &['E', 'W'],
+        'L' => &['N', 'E'],
+        'J' => &['N', 'W'],
+        '7' => &['S', 'W'],
+        'F' => &['S', 'E'],
+        _ => &[],
+    }
+}
 
-    (n_char, e_char, s_char, w_char)
+fn opposite(direction: char) -> char {
+    match direction {
+        'N' => 'S',
+        'E' => 'W',
+        'S' => 'N',
+        'W' => 'E',
+        _ => unreachable!(),
+    }
 }
 
-fn traverse(sketch: &Vec<Vec<char>>, s_row: usize, s_col: usize) -> Vec<(usize, usize)> {
+fn step(sketch: &[Vec<char>], pos: (usize, usize), direction: char) -> Option<(usize, usize)> {
+    let next = match direction {
+        'N' => (pos.0.checked_sub(1)?, pos.1),
+        'E' => (pos.0,                 pos.1 + 1),
+        'S' => (pos.0 + 1,             pos.1),
+        'W' => (pos.0,                 pos.1.checked_sub(1)?),
+        _ => unreachable!(),
+    };
+    if next.0 < sketch.len() && next.1 < sketch[next.0].len() {
+        Some(next)
+    } else {
+        None
+    }
+}
+
+fn traverse(sketch: &[Vec<char>], s_row: usize, s_col: usize) -> Result<Vec<(usize, usize)>, PipeError> {
     let mut steps: Vec<(usize, usize)> = Vec::new();
     let mut pos = (s_row, s_col);
     let mut direction = match sketch[pos.0][pos.1] {
@@ -37,12 +78,13 @@ fn traverse(sketch: &Vec<Vec<char>>, s_row: usize, s_col: usize) -> Vec<(usize,
         'J' => 'W',
         '7' => 'E',
         'F' => 'N',
-        _ => panic!(),
+        tile => return Err(PipeError::BrokenLoop { at: pos, tile }),
     };
 
     loop {
         steps.push(pos);
-        direction = match (sketch[pos.0][pos.1], direction) {
+        let tile = sketch[pos.0][pos.1];
+        direction = match (tile, direction) {
             ('|', 'N') => 'N',
             ('|', 'S') => 'S',
             ('-', 'E') => 'E',
@@ -55,21 +97,88 @@ fn traverse(sketch: &Vec<Vec<char>>, s_row: usize, s_col: usize) -> Vec<(usize,
             ('7', 'N') => 'W',
             ('F', 'N') => 'E',
             ('F', 'W') => 'S',
-            _ => panic!(),
-        };
-        pos = match direction {
-            'N' => (pos.0 - 1, pos.1),
-            'E' => (pos.0,     pos.1 + 1),
-            'S' => (pos.0 + 1, pos.1),
-            'W' => (pos.0,     pos.1 - 1),
-            _ => panic!(),
+            _ => return Err(PipeError::BrokenLoop { at: pos, tile }),
         };
+        pos = step(sketch, pos, direction).ok_or(PipeError::BrokenLoop { at: pos, tile })?;
         if pos == (s_row, s_col) {
+            let tile = sketch[pos.0][pos.1];
+            if !connections(tile).contains(&opposite(direction)) {
+                return Err(PipeError::BrokenLoop { at: pos, tile });
+            }
             break;
         }
     }
 
-    steps
+    Ok(steps)
+}
+
+// Picks a tile for 'S' among those whose both ends connect to a neighbour pointing back
+// at it. When more than one tile qualifies, the first one that closes the loop wins and
+// the rejected alternatives are reported.
+fn infer_start(sketch: &mut [Vec<char>], start: (usize, usize)) -> Result<Vec<char>, PipeError> {
+    let mutual: Vec<char> = ['N', 'E', 'S', 'W'].into_iter()
+        .filter(|&direction| match step(sketch, start, direction) {
+            Some(pos) => connections(sketch[pos.0][pos.1]).contains(&opposite(direction)),
+            None => false,
+        })
+        .collect();
+
+    let candidates: Vec<char> = TILES.into_iter()
+        .filter(|&tile| connections(tile).iter().all(|d| mutual.contains(d)))
+        .collect();
+
+    let mut first_error = None;
+    for &tile in candidates.iter() {
+        sketch[start.0][start.1] = tile;
+        match traverse(sketch, start.0, start.1) {
+            Ok(_) => return Ok(candidates.into_iter().filter(|&c| c != tile).collect()),
+            Err(e) => { first_error.get_or_insert(e); },
+        }
+    }
+
+    sketch[start.0][start.1] = 'S';
+    Err(first_error.unwrap_or(PipeError::NoLoop { start }))
+}
+
+// A sketch with 'S' replaced by the tile that closes the loop.
+#[derive(Debug)]
+struct Loaded {
+    sketch: Sketch,
+    start: (usize, usize),
+    // Other tiles 'S' connects as that do not close the loop.
+    rejected: Vec<char>,
+}
+
+impl Loaded {
+    fn ambiguity(&self) -> Option<String> {
+        if self.rejected.is_empty() {
+            return None;
+        }
+        Some(format!("start tile at {:?} also connects as {:?}; using '{}' which closes the loop",
+                     self.start, self.rejected, self.sketch[self.start.0][self.start.1]))
+    }
+}
+
+fn load_sketch(data: &str) -> Result<Loaded, PipeError> {
+    let mut sketch = Sketch::new();
+    let mut start = None;
+    for (row, line) in data.lines().enumerate() {
+        sketch.push(line.chars().collect());
+        if let Some(col) = line.find('S') {
+            start = Some((row, col));
+        }
+    }
+
+    let start = start.ok_or(PipeError::MissingStart)?;
+    let rejected = infer_start(&mut sketch, start)?;
+
+    Ok(Loaded{sketch, start, rejected})
+}
+
+fn parse_sketch(input_file_path: &str) -> Result<Loaded, PipeError> {
+    let data = std::fs::read_to_string(input_file_path)
+        .map_err(|e| PipeError::Io { message: e.to_string() })?;
+    load_sketch(&data)
 }
 
 enum ScanState {
@@ -108,46 +217,177 @@ fn scan(sketch: &Vec<Vec<char>>, pipeline: &Vec<(usize, usize)>) -> Vec<(usize,
     enclosed
 }
 
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut sketch: Vec<Vec<char>> = Vec::new();
-    let mut s_row = 0usize;
-    let mut s_col = 0usize;
-    for (row, line) in data.lines().enumerate() {
-        sketch.push(line.chars().collect());
-        if let Some(col) = line.find('S') {
-            s_row = row;
-            s_col = col;
-        }
-    }
+// Corner tiles of the loop, in traversal order.
+fn loop_vertices(sketch: &[Vec<char>], pipeline: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    pipeline.iter()
+        .filter(|pos| matches!(sketch[pos.0][pos.1], 'L' | 'J' | '7' | 'F'))
+        .cloned()
+        .collect()
+}
 
-    // N -> E -> S -> W
-    // | - L J 7 F
+// Shoelace gives the area of the polygon through the tile centres; Pick's theorem
+// (A = i + b/2 - 1) then yields the number of interior lattice points i, with every
+// loop tile counted as a boundary point b.
+fn pick_enclosed(vertices: &[(usize, usize)], boundary: usize) -> usize {
+    let twice_area: i64 = vertices.iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 as i64 * b.1 as i64 - b.0 as i64 * a.1 as i64)
+        .sum();
 
-    let s_char = match get_neighbors(&sketch, s_row, s_col) { // (sketch[s_row - 1][s_col], sketch[s_row][s_col + 1], sketch[s_row + 1][s_col], sketch[s_row][s_col -1]) {
-        ('F'|'|'|'7', _, 'J'|'|'|'L', _) => '|',
-        (_, 'J'|'-'|'7', _, 'L'|'-'|'F') => '-',
-        ('F'|'|'|'7', 'J'|'-'|'7', _, _) => 'L',
-        ('F'|'|'|'7', _, _, 'L'|'-'|'F') => 'J',
-        (_, _, 'J'|'|'|'L', 'L'|'-'|'F') => '7',
-        (_, 'J'|'-'|'7', 'J'|'|'|'L', _) => 'F',
-        _ => panic!(),
+    (twice_area.unsigned_abs() as usize + 2 - boundary) / 2
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Strategy {
+    Scan,
+    Pick,
+}
+
+fn solve(loaded: &Loaded, strategy: Strategy) -> Result<(usize, usize), PipeError> {
+    let Loaded{sketch, start, ..} = loaded;
+
+    let pipeline = traverse(sketch, start.0, start.1)?;
+    let enclosed = match strategy {
+        Strategy::Scan => scan(sketch, &pipeline).len(),
+        Strategy::Pick => pick_enclosed(&loop_vertices(sketch, &pipeline), pipeline.len()),
     };
 
-    sketch[s_row][s_col] = s_char;
+    Ok((pipeline.len() / 2, enclosed))
+}
+
+
+struct Options {
+    render: Option<String>,
+    output: Option<String>,
+    strategy: Strategy,
+}
+
+// Usage: day10 [--strategy scan|pick] [--render <input> [--output <file>]]
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options{render: None, output: None, strategy: Strategy::Scan};
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or(format!("missing value for '{}'", arg))?;
+        match arg.as_str() {
+            "--render" => options.render = Some(value.clone()),
+            "--output" => options.output = Some(value.clone()),
+            "--strategy" => options.strategy = match value.as_str() {
+                "scan" => Strategy::Scan,
+                "pick" => Strategy::Pick,
+                _ => return Err(format!("unknown strategy '{}'", value)),
+            },
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
 
-    let pipeline = traverse(&sketch, s_row, s_col);
+    Ok(options)
+}
+
+// Without an output file the map is printed to the terminal with ANSI colours.
+fn run_render(input_file_path: &str, output_file_path: Option<&str>) -> Result<(), String> {
+    let loaded = parse_sketch(input_file_path).map_err(|e| format!("{}: {}", input_file_path, e))?;
+    if let Some(warning) = loaded.ambiguity() {
+        eprintln!("Warning: {warning}");
+    }
+
+    let Loaded{sketch, start, ..} = loaded;
+    let pipeline = traverse(&sketch, start.0, start.1).map_err(|e| e.to_string())?;
     let enclosed = scan(&sketch, &pipeline);
 
-    (pipeline.len() / 2, enclosed.len())
+    match output_file_path {
+        Some(path) => {
+            let art = render::render(&sketch, start, &pipeline, &enclosed, false);
+            std::fs::write(path, art).map_err(|e| format!("cannot write '{}': {}", path, e))?;
+        },
+        None => print!("{}", render::render(&sketch, start, &pipeline, &enclosed, true)),
+    }
+    println!("loop length: {}, enclosed: {}", pipeline.len(), enclosed.len());
+
+    Ok(())
 }
 
-fn main() {
-    println!("{:?}", solution("day10/input/test_a.txt"));
-    println!("{:?}", solution("day10/input/test_b.txt"));
-    println!("{:?}", solution("day10/input/test_c.txt"));
-    println!("{:?}", solution("day10/input/test_d.txt"));
-    println!("{:?}", solution("day10/input/test_e.txt"));
-    println!("{:?}", solution("day10/input/input.txt"));
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        },
+    };
+
+    if let Some(input_file_path) = options.render {
+        if let Err(e) = run_render(&input_file_path, options.output.as_deref()) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let mut exit_code = ExitCode::SUCCESS;
+    for input in ["test_a", "test_b", "test_c", "test_d", "test_e", "input"] {
+        let input_file_path = format!("day10/input/{}.txt", input);
+        let loaded = match parse_sketch(&input_file_path) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("{}: {}", input_file_path, e);
+                exit_code = ExitCode::from(1);
+                continue;
+            },
+        };
+        if let Some(warning) = loaded.ambiguity() {
+            eprintln!("{}: {}", input_file_path, warning);
+        }
+        match solve(&loaded, options.strategy) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("{}: {}", input_file_path, e);
+                exit_code = ExitCode::from(1);
+            },
+        }
+    }
+    exit_code
 }
 
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn solution(input_file_path: &str, strategy: Strategy) -> Result<(usize, usize), PipeError> {
+        solve(&parse_sketch(input_file_path)?, strategy)
+    }
+
+    #[test]
+    fn test_ambiguous_start() {
+        let loaded = load_sketch(".....\n-S-7.\n.|.|.\n.L-J.\n").unwrap();
+
+        assert_eq!(loaded.start, (1, 1));
+        assert_eq!(loaded.sketch[1][1], 'F');
+        assert_eq!(loaded.rejected, vec!['-', '7']);
+        assert!(loaded.ambiguity().unwrap().contains("using 'F'"));
+        assert_eq!(traverse(&loaded.sketch, 1, 1).unwrap().len(), 8);
+
+        assert_eq!(load_sketch(&std::fs::read_to_string("input/test_a.txt").unwrap()).unwrap().ambiguity(), None);
+    }
+
+    #[test]
+    fn test_malformed_maps() {
+        assert!(matches!(parse_sketch("input/missing.txt").unwrap_err(), PipeError::Io { .. }));
+        assert_eq!(load_sketch("F-7\n|.|\nL-J\n").unwrap_err(), PipeError::MissingStart);
+        assert_eq!(load_sketch("...\n.S.\n...\n").unwrap_err(), PipeError::NoLoop { start: (1, 1) });
+        assert_eq!(load_sketch("S-7\n|.|\nL-.\n").unwrap_err(),
+                   PipeError::BrokenLoop { at: (2, 2), tile: '.' });
+    }
+
+    #[test]
+    fn test_enclosed_strategies_agree() {
+        for input in ["test_a", "test_b", "test_c", "test_d", "test_e"] {
+            let input_file_path = format!("input/{}.txt", input);
+            assert_eq!(solution(&input_file_path, Strategy::Scan),
+                       solution(&input_file_path, Strategy::Pick));
+        }
+        assert_eq!(solution("input/test_e.txt", Strategy::Pick), Ok((80, 10)));
+    }
+}
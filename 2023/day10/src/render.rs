@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+const RESET: &str = "\x1b[0m";
+const LOOP_COLOUR: &str = "\x1b[36m";
+const START_COLOUR: &str = "\x1b[1;33m";
+const ENCLOSED_COLOUR: &str = "\x1b[1;42m";
+
+fn box_char(chr: char) -> char {
+    match chr {
+        '|' => '│',
+        '-' => '─',
+        'L' => '└',
+        'J' => '┘',
+        '7' => '┐',
+        'F' => '┌',
+        _ => chr,
+    }
+}
+
+// Draws the loop with box-drawing characters, marks enclosed tiles with 'I' and blanks
+// everything else, including junk pipes that are not part of the loop.
+pub fn render(sketch: &[Vec<char>], start: (usize, usize), pipeline: &[(usize, usize)],
+              enclosed: &[(usize, usize)], colour: bool) -> String {
+    let pipes: HashSet<(usize, usize)> = pipeline.iter().cloned().collect();
+    let inside: HashSet<(usize, usize)> = enclosed.iter().cloned().collect();
+    let mut output = String::new();
+
+    for (row, line) in sketch.iter().enumerate() {
+        for (col, &chr) in line.iter().enumerate() {
+            let pos = (row, col);
+            let (tile, paint) = if pipes.contains(&pos) {
+                let paint = if pos == start { START_COLOUR } else { LOOP_COLOUR };
+                (box_char(chr), paint)
+            } else if inside.contains(&pos) {
+                ('I', ENCLOSED_COLOUR)
+            } else {
+                (' ', "")
+            };
+
+            if colour && !paint.is_empty() {
+                output.push_str(paint);
+                output.push(tile);
+                output.push_str(RESET);
+            } else {
+                output.push(tile);
+            }
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+    use crate::{parse_sketch, scan, traverse};
+
+    #[test]
+    fn test_render_plain() {
+        let crate::Loaded{sketch, start, ..} = parse_sketch("input/test_c.txt").unwrap();
+        let pipeline = traverse(&sketch, start.0, start.1).unwrap();
+        let enclosed = scan(&sketch, &pipeline);
+
+        let expected = "           \n\
+                        \x20┌───────┐ \n\
+                        \x20│┌─────┐│ \n\
+                        \x20││     ││ \n\
+                        \x20││     ││ \n\
+                        \x20│└─┐ ┌─┘│ \n\
+                        \x20│II│ │II│ \n\
+                        \x20└──┘ └──┘ \n\
+                        \x20          \n";
+
+        assert_eq!(render(&sketch, start, &pipeline, &enclosed, false), expected);
+    }
+}
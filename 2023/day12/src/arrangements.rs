@@ -0,0 +1,174 @@
+// Line solving over a row of springs, as in a nonogram: a state (i, g) means cells
+// before i are decided, groups before g are placed, and cell i is free to start a group.
+
+// Whether groups[g] can be placed starting at cell i, including the '.' that must
+// follow it unless it ends the row.
+fn fits(template: &[char], i: usize, len: usize) -> bool {
+    i + len <= template.len() &&
+    !template[i..i + len].contains(&'.') &&
+    template.get(i + len) != Some(&'#')
+}
+
+// State reached after placing a group of len cells at i.
+fn after_group(template: &[char], i: usize, len: usize) -> usize {
+    (i + len + 1).min(template.len())
+}
+
+// feasible[i][g]: template[i..] can be completed with groups[g..].
+fn feasibility(template: &[char], groups: &[usize]) -> Vec<Vec<bool>> {
+    let n = template.len();
+    let m = groups.len();
+    let mut feasible = vec![vec![false; m + 1]; n + 1];
+    feasible[n][m] = true;
+
+    for i in (0..n).rev() {
+        for g in 0..=m {
+            let dot = template[i] != '#' && feasible[i + 1][g];
+            let group = g < m && template[i] != '.' && fits(template, i, groups[g]) &&
+                        feasible[after_group(template, i, groups[g])][g + 1];
+            feasible[i][g] = dot || group;
+        }
+    }
+
+    feasible
+}
+
+// Lazily enumerates every concrete arrangement of a row, '#' placements first.
+pub struct Arrangements<'a> {
+    template: &'a [char],
+    groups: &'a [usize],
+    feasible: Vec<Vec<bool>>,
+    stack: Vec<(usize, usize, Vec<char>)>,
+}
+
+impl<'a> Arrangements<'a> {
+    pub fn new(template: &'a [char], groups: &'a [usize]) -> Self {
+        let feasible = feasibility(template, groups);
+        let stack = if feasible[0][0] { vec![(0, 0, Vec::new())] } else { Vec::new() };
+        Self{template, groups, feasible, stack}
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = Vec<char>;
+
+    fn next(&mut self) -> Option<Vec<char>> {
+        while let Some((i, g, cells)) = self.stack.pop() {
+            if i == self.template.len() {
+                return Some(cells);
+            }
+
+            if self.template[i] != '#' && self.feasible[i + 1][g] {
+                let mut cells = cells.clone();
+                cells.push('.');
+                self.stack.push((i + 1, g, cells));
+            }
+
+            if g < self.groups.len() && self.template[i] != '.' && fits(self.template, i, self.groups[g]) {
+                let next = after_group(self.template, i, self.groups[g]);
+                if self.feasible[next][g + 1] {
+                    let mut cells = cells;
+                    cells.extend(std::iter::repeat_n('#', self.groups[g]));
+                    cells.resize(next, '.');
+                    self.stack.push((next, g + 1, cells));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+// Replaces every '?' that is the same in all valid arrangements with its forced value,
+// or returns None if the row has no valid arrangement.
+pub fn resolve(template: &[char], groups: &[usize]) -> Option<Vec<char>> {
+    let n = template.len();
+    let m = groups.len();
+    let feasible = feasibility(template, groups);
+    if !feasible[0][0] {
+        return None;
+    }
+
+    let mut reachable = vec![vec![false; m + 1]; n + 1];
+    reachable[0][0] = true;
+    let mut can_dot = vec![false; n];
+    let mut can_hash = vec![false; n];
+
+    for i in 0..n {
+        for g in 0..=m {
+            if !reachable[i][g] {
+                continue;
+            }
+            if template[i] != '#' && feasible[i + 1][g] {
+                reachable[i + 1][g] = true;
+                can_dot[i] = true;
+            }
+            if g < m && template[i] != '.' && fits(template, i, groups[g]) {
+                let next = after_group(template, i, groups[g]);
+                if feasible[next][g + 1] {
+                    reachable[next][g + 1] = true;
+                    can_hash[i..i + groups[g]].fill(true);
+                    if next > i + groups[g] {
+                        can_dot[i + groups[g]] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    Some(template.iter().enumerate().map(|(i, &chr)| match (chr, can_hash[i], can_dot[i]) {
+        ('?', true, false) => '#',
+        ('?', false, true) => '.',
+        _ => chr,
+    }).collect())
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+    use std::collections::HashSet;
+    use crate::count::Count;
+    use crate::count_arrangements;
+
+    fn parse(line: &str) -> (Vec<char>, Vec<usize>) {
+        let (template, groups) = line.split_once(' ').unwrap();
+        (template.chars().collect(), groups.split(',').map(|d| d.parse().unwrap()).collect())
+    }
+
+    #[test]
+    fn test_enumeration_matches_count() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        for line in data.lines() {
+            let (template, groups) = parse(line);
+            let arrangements: HashSet<Vec<char>> = Arrangements::new(&template, &groups).collect();
+
+            assert_eq!(Count::from(arrangements.len() as u64), count_arrangements(&template, &groups), "{}", line);
+            for arrangement in arrangements {
+                assert_eq!(arrangement.len(), template.len());
+                assert!(template.iter().zip(arrangement.iter()).all(|(&t, &a)| t == '?' || t == a));
+            }
+        }
+    }
+
+    #[test]
+    fn test_enumeration_is_lazy() {
+        let (template, groups) = parse("?????????????????????????????????????????????????? 1,1,1,1,1");
+        let first: Vec<Vec<char>> = Arrangements::new(&template, &groups).take(2).collect();
+
+        assert_eq!(first[0].iter().collect::<String>(), "#.#.#.#.#.".to_string() + &".".repeat(40));
+        assert_eq!(first.len(), 2);
+    }
+
+    #[test]
+    fn test_resolve() {
+        let (template, groups) = parse("???.### 1,1,3");
+        assert_eq!(resolve(&template, &groups), Some("#.#.###".chars().collect()));
+
+        let (template, groups) = parse("?###???????? 3,2,1");
+        assert_eq!(resolve(&template, &groups), Some(".###.???????".chars().collect()));
+
+        let (template, groups) = parse("#.# 3");
+        assert_eq!(resolve(&template, &groups), None);
+    }
+}
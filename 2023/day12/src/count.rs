@@ -0,0 +1,104 @@
+use std::fmt;
+
+// Unsigned arbitrary-precision counter. Counting arrangements only ever adds, so that
+// is all it supports besides conversion and printing.
+#[derive(Clone, Default, Eq, PartialEq)]
+pub struct Count {
+    limbs: Vec<u64>,
+}
+
+impl Count {
+    pub fn zero() -> Self {
+        Self{limbs: Vec::new()}
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn add(&mut self, other: &Count) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (sum, c1) = limb.overflowing_add(other.limbs.get(i).copied().unwrap_or(0));
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = c1 || c2;
+            if !carry && i >= other.limbs.len() {
+                break;
+            }
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+}
+
+impl From<u64> for Count {
+    fn from(value: u64) -> Self {
+        if value == 0 { Self::zero() } else { Self{limbs: vec![value]} }
+    }
+}
+
+impl fmt::Display for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+
+        let mut limbs = self.limbs.clone();
+        let mut chunks = Vec::new();
+        while !limbs.is_empty() {
+            let mut remainder = 0u128;
+            for limb in limbs.iter_mut().rev() {
+                let value = (remainder << 64) | *limb as u128;
+                *limb = (value / CHUNK) as u64;
+                remainder = value % CHUNK;
+            }
+            chunks.push(remainder as u64);
+            while limbs.last() == Some(&0) {
+                limbs.pop();
+            }
+        }
+
+        match chunks.split_last() {
+            None => write!(f, "0"),
+            Some((first, rest)) => {
+                write!(f, "{}", first)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:019}", chunk)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Count {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_add_and_display() {
+        let mut count = Count::from(u64::MAX);
+        count.add(&Count::from(1));
+        assert_eq!(count.to_string(), "18446744073709551616");
+
+        let mut doubled = count.clone();
+        for _ in 0..64 {
+            let copy = doubled.clone();
+            doubled.add(&copy);
+        }
+        assert_eq!(doubled.to_string(), "340282366920938463463374607431768211456");
+
+        assert_eq!(Count::zero().to_string(), "0");
+        assert_eq!(Count::from(10_000_000_000_000_000_000).to_string(), "10000000000000000000");
+    }
+}
@@ -1,73 +1,169 @@
 use std::iter::once;
-use std::collections::HashMap;
-
-fn get_arrangements<'a, 'b>(template: &'a [char], groups: &'a [usize], cache: &'b mut HashMap<(&'a [char], &'a [usize]), usize>) -> usize {
-
-    if !groups.is_empty() && template.len() < groups.iter().sum::<usize>() + groups.len() - 1 {
-        return 0;
+use std::process::ExitCode;
+
+mod arrangements;
+mod count;
+
+use arrangements::Arrangements;
+use count::Count;
+
+// Runs a DP over the cells, with states (group, run) meaning groups before `group` are
+// complete and the current run of '#' towards groups[group] is `run` cells long.
+fn count_arrangements(template: &[char], groups: &[usize]) -> Count {
+    let m = groups.len();
+    let offsets: Vec<usize> = once(0).chain(groups.iter().scan(0, |acc, &len| { *acc += len + 1; Some(*acc) })).collect();
+    let index = |group: usize, run: usize| offsets[group] + run;
+
+    let mut counts = vec![Count::zero(); offsets[m] + 1];
+    counts[index(0, 0)] = Count::from(1);
+
+    for &cell in template {
+        let mut next = vec![Count::zero(); counts.len()];
+        for group in 0..=m {
+            let max_run = if group < m { groups[group] } else { 0 };
+            for run in 0..=max_run {
+                let count = &counts[index(group, run)];
+                if count.is_zero() {
+                    continue;
+                }
+                if cell != '#' {
+                    if run == 0 {
+                        next[index(group, 0)].add(count);
+                    } else if run == groups[group] {
+                        next[index(group + 1, 0)].add(count);
+                    }
+                }
+                if cell != '.' && group < m && run < groups[group] {
+                    next[index(group, run + 1)].add(count);
+                }
+            }
+        }
+        counts = next;
     }
 
-    if groups.is_empty() && template.contains(&'#') {
-        return 0;
+    let mut total = counts[index(m, 0)].clone();
+    if m > 0 {
+        total.add(&counts[index(m - 1, groups[m - 1])]);
     }
+    total
+}
 
-    if groups.is_empty() && !template.contains(&'#') {
-        return 1;
-    }
+fn unfold(template: &[char], groups: &[usize], factor: usize) -> (Vec<char>, Vec<usize>) {
+    let expanded_template: Vec<char> = template.iter().chain(once(&'?')).cycle().take((template.len() + 1) * factor - 1).cloned().collect();
+    let expanded_groups: Vec<usize> = groups.iter().cycle().take(groups.len() * factor).cloned().collect();
+    (expanded_template, expanded_groups)
+}
 
-    if cache.contains_key(&(template, groups)) {
-        return cache.get(&(template, groups)).unwrap().clone();
-    }
+fn parse_row(line: &str) -> Result<(Vec<char>, Vec<usize>), String> {
+    let tokens = line.split_once(' ').ok_or(format!("malformed row '{}'", line))?;
+    let template: Vec<char> = tokens.0.chars().collect();
+    let groups: Vec<usize> = tokens.1.split(',')
+        .map(|d| d.parse::<usize>().map_err(|_| format!("malformed groups in '{}'", line)))
+        .collect::<Result<_, _>>()?;
+    Ok((template, groups))
+}
 
-    let mut num_arrangements = 0usize;
+fn solution(input_file_path: &str, factor: usize) -> Result<(Count, Count), String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    let mut part_a = Count::zero();
+    let mut part_b = Count::zero();
+
+    for line in data.lines() {
+        let (template, groups) = parse_row(line)?;
 
-    if ['.', '?'].contains(&template[0]) {
-        num_arrangements += get_arrangements(&template[1..], groups, cache);
+        part_a.add(&count_arrangements(&template, &groups));
+
+        let (expanded_template, expanded_groups) = unfold(&template, &groups, factor);
+        part_b.add(&count_arrangements(&expanded_template, &expanded_groups));
     }
 
-    if ['#', '?'].contains(&template[0]) {
-        let dotpos = template.iter().position(|&c| c == '.');
-        if dotpos.is_none() || dotpos.unwrap() >= groups[0] {
-            if template.len() > groups[0] {
-                if ['.', '?'].contains(&template[groups[0]]) {
-                    num_arrangements += get_arrangements(&template[groups[0] + 1..], &groups[1..], cache);
-                }
-            } else {
-                num_arrangements += get_arrangements(&template[groups[0]..], &groups[1..], cache);
-            }
+    Ok((part_a, part_b))
+}
+
+// Lists up to n concrete arrangements per row, along with the row's forced cells.
+fn run_arrangements(input_file_path: &str, limit: usize) -> Result<(), String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+
+    for line in data.lines() {
+        let (template, groups) = parse_row(line)?;
+
+        match arrangements::resolve(&template, &groups) {
+            Some(resolved) => println!("{} -> forced {}", line, resolved.iter().collect::<String>()),
+            None => println!("{} -> no valid arrangement", line),
+        }
+        for arrangement in Arrangements::new(&template, &groups).take(limit) {
+            println!("  {}", arrangement.iter().collect::<String>());
         }
     }
 
-    cache.insert((template, groups), num_arrangements);
+    Ok(())
+}
 
-    num_arrangements
+// Usage: day12 --arrangements <input> [--limit <n>]
+//        day12 --unfold <factor> <input>
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, path] if flag == "--arrangements" => run_arrangements(path, 10),
+        [flag, path, limit_flag, limit] if flag == "--arrangements" && limit_flag == "--limit" =>
+            run_arrangements(path, limit.parse().map_err(|_| format!("invalid --limit '{}'", limit))?),
+        [flag, factor, path] if flag == "--unfold" => {
+            let factor: usize = factor.parse().map_err(|_| format!("invalid --unfold '{}'", factor))?;
+            if factor == 0 {
+                return Err("unfold factor must be at least 1".to_string());
+            }
+            println!("{:?}", solution(path, factor)?);
+            Ok(())
+        },
+        _ => Err("expected --arrangements <input> [--limit <n>] or --unfold <factor> <input>".to_string()),
+    }
 }
 
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut part_a = 0usize;
-    let mut part_b = 0usize;
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
 
-    for line in data.lines() {
-        let tokens = line.split_once(' ').unwrap();
-        let template: Vec<char> = tokens.0.chars().collect();
-        let groups: Vec<usize> = tokens.1.split(',').map(|d| d.parse::<usize>().unwrap()).collect();
+    for input_file_path in ["day12/input/test.txt", "day12/input/input.txt"] {
+        match solution(input_file_path, 5) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
+}
 
-        let mut cache: HashMap<(&[char], &[usize]), usize> = HashMap::new();
 
-        part_a += get_arrangements(&template, &groups, &mut cache);
+#[cfg(test)]
+mod unit_tests {
 
-        let expanded_template: Vec<char> = template.iter().chain(once(&'?')).cycle().take(template.len() * 5 + 4).cloned().collect();
-        let expanded_groups: Vec<usize> = groups.iter().cycle().take(groups.len() * 5).cloned().collect();
+    use super::*;
 
-        part_b += get_arrangements(&expanded_template, &expanded_groups, &mut cache);
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt", 1), Ok((Count::from(21), Count::from(21))));
+        assert_eq!(solution("input/test.txt", 5), Ok((Count::from(21), Count::from(525152))));
     }
 
-    (part_a, part_b)
-}
+    #[test]
+    fn test_unfold_past_u64() {
+        let (part_a, part_b) = solution("input/test.txt", 20).unwrap();
+        assert_eq!(part_a, Count::from(21));
+        assert_eq!(part_b.to_string(), "221684358542356307854028");
+    }
 
-fn main() {
-    println!("{:?}", solution("day12/input/test.txt"));
-    println!("{:?}", solution("day12/input/input.txt"));
+    #[test]
+    fn test_malformed_rows() {
+        assert_eq!(parse_row("???.### 1,1,3"), Ok(("???.###".chars().collect(), vec![1, 1, 3])));
+        assert!(parse_row("???.###").is_err());
+        assert!(parse_row("???.### 1,x,3").is_err());
+        assert!(solution("input/missing.txt", 5).unwrap_err().starts_with("cannot read"));
+    }
 }
-
@@ -0,0 +1,72 @@
+// Brent's cycle detection. States are only compared for equality, so unlike hashing
+// the detected cycle is exact. Returns (prefix_len, cycle_len): the state after
+// prefix_len steps is the first one that recurs, every cycle_len steps.
+pub fn find_cycle<T: Clone + PartialEq>(initial: &T, step: impl Fn(&T) -> T) -> (usize, usize) {
+    let mut power = 1usize;
+    let mut cycle_len = 1usize;
+    let mut tortoise = initial.clone();
+    let mut hare = step(initial);
+
+    while tortoise != hare {
+        if power == cycle_len {
+            tortoise = hare.clone();
+            power *= 2;
+            cycle_len = 0;
+        }
+        hare = step(&hare);
+        cycle_len += 1;
+    }
+
+    let mut tortoise = initial.clone();
+    let mut hare = initial.clone();
+    for _ in 0..cycle_len {
+        hare = step(&hare);
+    }
+
+    let mut prefix_len = 0usize;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix_len += 1;
+    }
+
+    (prefix_len, cycle_len)
+}
+
+// The state after n steps, skipping whole cycles once the sequence starts repeating.
+pub fn state_after<T: Clone + PartialEq>(initial: &T, step: impl Fn(&T) -> T, n: usize) -> T {
+    let (prefix_len, cycle_len) = find_cycle(initial, &step);
+    let remaining = if n <= prefix_len { n } else { prefix_len + (n - prefix_len) % cycle_len };
+
+    let mut state = initial.clone();
+    for _ in 0..remaining {
+        state = step(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    // 20, 17, 14, 11, 8, 2, 6, 0, 4, 8, 2, ...
+    fn step(x: &u32) -> u32 {
+        if *x >= 10 { x - 3 } else { (x + 4) % 10 }
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        assert_eq!(find_cycle(&20, step), (4, 5));
+        assert_eq!(find_cycle(&8, step), (0, 5));
+        assert_eq!(find_cycle(&0, |x: &u32| *x), (0, 1));
+    }
+
+    #[test]
+    fn test_state_after() {
+        assert_eq!(state_after(&20, step, 0), 20);
+        assert_eq!(state_after(&20, step, 3), 11);
+        assert_eq!(state_after(&20, step, 9), 8);
+        assert_eq!(state_after(&20, step, 1_000_000), 2);
+    }
+}
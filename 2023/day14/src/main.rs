@@ -1,145 +1,65 @@
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+mod cycle;
+mod platform;
 
-#[derive(PartialEq)]
-enum Slope {
-    Ascending,
-    Descending,
-}
+use std::process::ExitCode;
 
-fn tilt_verti(mut grid: Vec<Vec<char>>, slope: Slope) -> Vec<Vec<char>> {
-    let size = grid.len();
-    let mut next_free = vec![0usize; size];
-    for row in 0..size {
-        for col in 0..size {
-            match grid[row][col] {
-                '#' => next_free[col] = row + 1,
-                'O' if slope == Slope::Ascending => {
-                    if next_free[col] != row {
-                        grid[next_free[col]][col] = 'O';
-                        grid[row][col] = '.';
-                    }
-                    next_free[col] += 1;
-                },
-                '.' if slope == Slope::Descending => {
-                    if next_free[col] != row {
-                        grid[next_free[col]][col] = '.';
-                        grid[row][col] = 'O';
-                    }
-                    next_free[col] += 1;
-                },
-                _ => (),
-            }
-        }
-    }
+use platform::{Platform, TiltDir};
 
-    grid
-}
+fn solution(input_file_path: &str) -> Result<(u64, u64), String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    let (platform, walls) = Platform::parse(&data)?;
 
-fn tilt_horiz(mut grid: Vec<Vec<char>>, slope: Slope) -> Vec<Vec<char>> {
-    for row in grid.iter_mut() {
-        let mut next_free = 0usize;
-        for col in 0..row.len() {
-            match &row[col] {
-                &'#' => next_free = col + 1,
-                &'O' if slope == Slope::Ascending => {
-                    if col != next_free {
-                        row[col] = '.';
-                        row[next_free] = 'O';
-                    }
-                    next_free += 1;
-                },
-                &'.' if slope == Slope::Descending => {
-                    if col != next_free {
-                        row[col] = 'O';
-                        row[next_free] = '.';
-                    }
-                    next_free += 1;
-                },
-                _ => (),
-            }
-        }
-    }
-
-    grid
-}
+    let part_a = platform.tilt(&walls, TiltDir::N).load();
 
-enum TiltDir {
-    N,
-    E,
-    S,
-    W,
-}
-
-fn tilt(grid: Vec<Vec<char>>, dir: TiltDir) -> Vec<Vec<char>> {
-    match dir {
-        TiltDir::N => tilt_verti(grid, Slope::Ascending),
-        TiltDir::S => tilt_verti(grid, Slope::Descending),
+    let num_cycles = 1_000_000_000;
+    let part_b = cycle::state_after(&platform, |p: &Platform| p.spin(&walls), num_cycles).load();
 
-        TiltDir::W => tilt_horiz(grid, Slope::Ascending),
-        TiltDir::E => tilt_horiz(grid, Slope::Descending),
-    }
+    Ok((part_a, part_b))
 }
 
-fn get_load(grid: &Vec<Vec<char>>) -> u64 {
-    let mut load = 0u64;
-    let num_rows = grid.len() as u64;
-    for (row, line) in grid.iter().enumerate() {
-        for chr in line.iter() {
-            match chr {
-                'O' => load += num_rows - row as u64,
-                _ => (),
-            }
+fn main() -> ExitCode {
+    for input_file_path in ["day14/input/test.txt", "day14/input/input.txt"] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
         }
     }
-
-    load
+    ExitCode::SUCCESS
 }
 
-fn solution(input_file_path: &str) -> (u64, u64) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
-
-    assert!(grid.len() > 0);
-    assert!(grid.len() == grid[0].len());
+#[cfg(test)]
+mod unit_tests {
 
-    let part_a = get_load(&tilt(grid.clone(), TiltDir::N));
-
-    let mut part_b = 0u64;
-    let mut load_cache: HashMap<u64, (u64, usize)> = HashMap::new(); // grid_hash -> (grid_load, prev_cycle)
-
-    let num_cycles = 1_000_000_000;
+    use super::*;
 
-    for cycle in 1..=num_cycles {
-        grid = tilt(grid, TiltDir::N);
-        grid = tilt(grid, TiltDir::W);
-        grid = tilt(grid, TiltDir::S);
-        grid = tilt(grid, TiltDir::E);
-
-        let mut hasher = DefaultHasher::new();
-        grid.hash(&mut hasher);
-        let hash = hasher.finish();
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((136, 64)));
+    }
 
-        if load_cache.contains_key(&hash) {
-            let (load, prev_cycle) = load_cache.get(&hash).unwrap();
-            let cadence_len = cycle - prev_cycle;
-            let remaining_cycles = num_cycles - cycle;
-            if remaining_cycles % cadence_len == 0 {
-                part_b = *load;
-                break;
+    #[test]
+    fn test_cycle_matches_brute_force() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let (platform, walls) = Platform::parse(&data).unwrap();
+        let spin = |p: &Platform| p.spin(&walls);
+
+        let mut states = vec![platform.clone()];
+        let (prefix_len, cycle_len) = loop {
+            let next = spin(states.last().unwrap());
+            if let Some(first) = states.iter().position(|state| *state == next) {
+                break (first, states.len() - first);
             }
+            states.push(next);
+        };
+        assert_eq!(cycle::find_cycle(&platform, spin), (prefix_len, cycle_len));
+
+        let mut state = platform.clone();
+        for n in 0..40 {
+            assert_eq!(cycle::state_after(&platform, spin, n), state, "{}", n);
+            state = spin(&state);
         }
-
-        load_cache.entry(hash)
-                  .and_modify(|(_, prev_cycle)| *prev_cycle = cycle)
-                  .or_insert((get_load(&grid), cycle));
     }
-
-    (part_a, part_b)
-}
-
-fn main() {
-    println!("{:?}", solution("day14/input/test.txt"));
-    println!("{:?}", solution("day14/input/input.txt"));
 }
@@ -0,0 +1,187 @@
+// Rolling rocks as bitmasks, one u128 per line, so both dimensions are capped at 128.
+// Rows keep a bit per column and columns a bit per row; bit 0 is the north or west edge.
+
+pub const MAX_SIZE: usize = 128;
+
+#[derive(Clone, Copy)]
+pub enum TiltDir {
+    N,
+    E,
+    S,
+    W,
+}
+
+// Stretches of a line between cube rocks; every rolling rock within a segment ends up
+// packed against one of its ends.
+type Segments = Vec<Vec<u128>>;
+
+fn fill(n: u32) -> u128 {
+    if n == 128 { u128::MAX } else { (1u128 << n) - 1 }
+}
+
+fn segments(walls: &[u128], len: usize) -> Segments {
+    walls.iter().map(|&wall| {
+        let mut line_segments = Vec::new();
+        let mut start = 0usize;
+        for bit in 0..=len {
+            if bit == len || wall & (1 << bit) != 0 {
+                if bit > start {
+                    line_segments.push(fill((bit - start) as u32) << start);
+                }
+                start = bit + 1;
+            }
+        }
+        line_segments
+    }).collect()
+}
+
+fn transpose(lines: &[u128], len: usize) -> Vec<u128> {
+    let mut transposed = vec![0u128; len];
+    for (i, &line) in lines.iter().enumerate() {
+        let mut bits = line;
+        while bits != 0 {
+            transposed[bits.trailing_zeros() as usize] |= 1 << i;
+            bits &= bits - 1;
+        }
+    }
+    transposed
+}
+
+fn pack(lines: &mut [u128], segments: &Segments, ascending: bool) {
+    for (line, line_segments) in lines.iter_mut().zip(segments) {
+        let mut packed = 0u128;
+        for &segment in line_segments {
+            let count = (*line & segment).count_ones();
+            packed |= if ascending {
+                fill(count) << segment.trailing_zeros()
+            } else {
+                fill(count) << (MAX_SIZE as u32 - segment.leading_zeros() - count)
+            };
+        }
+        *line = packed;
+    }
+}
+
+pub struct Walls {
+    row_segments: Segments,
+    col_segments: Segments,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Platform {
+    rows: usize,
+    cols: usize,
+    rocks: Vec<u128>,
+}
+
+impl Platform {
+    pub fn parse(data: &str) -> Result<(Self, Walls), String> {
+        let grid: Vec<&[u8]> = data.lines().map(str::as_bytes).collect();
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |line| line.len());
+        if rows == 0 || cols == 0 {
+            return Err("empty platform".to_string());
+        }
+        if rows > MAX_SIZE || cols > MAX_SIZE {
+            return Err(format!("platform is {}x{}, at most {2}x{2} is supported", rows, cols, MAX_SIZE));
+        }
+        if let Some(row) = grid.iter().position(|line| line.len() != cols) {
+            return Err(format!("row {} has {} cells, expected {}", row + 1, grid[row].len(), cols));
+        }
+        if let Some(&c) = grid.iter().flat_map(|line| line.iter()).find(|c| !b".#O".contains(c)) {
+            return Err(format!("unknown tile '{}'", c as char));
+        }
+
+        let mask = |chr: u8| -> Vec<u128> {
+            grid.iter().map(|line| {
+                line.iter().enumerate()
+                    .filter(|(_, &c)| c == chr)
+                    .fold(0u128, |mask, (col, _)| mask | 1 << col)
+            }).collect()
+        };
+
+        let walls = mask(b'#');
+        let platform = Self{rows, cols, rocks: mask(b'O')};
+        let walls = Walls{
+            row_segments: segments(&walls, cols),
+            col_segments: segments(&transpose(&walls, cols), rows),
+        };
+
+        Ok((platform, walls))
+    }
+
+    pub fn tilt(&self, walls: &Walls, dir: TiltDir) -> Self {
+        let mut platform = self.clone();
+        match dir {
+            TiltDir::W => pack(&mut platform.rocks, &walls.row_segments, true),
+            TiltDir::E => pack(&mut platform.rocks, &walls.row_segments, false),
+            TiltDir::N | TiltDir::S => {
+                let mut columns = transpose(&self.rocks, self.cols);
+                pack(&mut columns, &walls.col_segments, matches!(dir, TiltDir::N));
+                platform.rocks = transpose(&columns, self.rows);
+            },
+        }
+        platform
+    }
+
+    pub fn spin(&self, walls: &Walls) -> Self {
+        self.tilt(walls, TiltDir::N)
+            .tilt(walls, TiltDir::W)
+            .tilt(walls, TiltDir::S)
+            .tilt(walls, TiltDir::E)
+    }
+
+    pub fn load(&self) -> u64 {
+        self.rocks.iter().enumerate()
+            .map(|(row, line)| line.count_ones() as u64 * (self.rows - row) as u64)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn render(platform: &Platform, data: &str) -> String {
+        data.lines().enumerate().map(|(row, line)| {
+            line.chars().enumerate().map(|(col, chr)| match chr {
+                '#' => '#',
+                _ if platform.rocks[row] & (1 << col) != 0 => 'O',
+                _ => '.',
+            }).collect::<String>() + "\n"
+        }).collect()
+    }
+
+    #[test]
+    fn test_non_square_tilts() {
+        let data = "O.#.O\n\
+                    .O..O\n\
+                    O.O#.\n";
+        let (platform, walls) = Platform::parse(data).unwrap();
+
+        assert_eq!(render(&platform.tilt(&walls, TiltDir::N), data), "OO#.O\nO.O.O\n...#.\n");
+        assert_eq!(render(&platform.tilt(&walls, TiltDir::S), data), "..#..\nO...O\nOOO#O\n");
+        assert_eq!(render(&platform.tilt(&walls, TiltDir::W), data), "O.#O.\nOO...\nOO.#.\n");
+        assert_eq!(render(&platform.tilt(&walls, TiltDir::E), data), ".O#.O\n...OO\n.OO#.\n");
+        assert_eq!(platform.tilt(&walls, TiltDir::N).load(), 3 * 3 + 3 * 2);
+    }
+
+    #[test]
+    fn test_full_width_line() {
+        let data = ".".repeat(MAX_SIZE - 1) + "O";
+        let (platform, walls) = Platform::parse(&data).unwrap();
+
+        assert_eq!(platform.tilt(&walls, TiltDir::W).rocks, vec![1]);
+        assert_eq!(platform.tilt(&walls, TiltDir::E).rocks, vec![1 << 127]);
+    }
+
+    #[test]
+    fn test_malformed_platforms() {
+        assert!(Platform::parse("").is_err());
+        assert!(Platform::parse(&".".repeat(MAX_SIZE + 1)).is_err());
+        assert!(Platform::parse(&"O\n".repeat(MAX_SIZE + 1)).is_err());
+        assert_eq!(Platform::parse("O.#\n.O\n").err(), Some("row 2 has 2 cells, expected 3".to_string()));
+        assert!(Platform::parse("O.x\n").is_err());
+    }
+}
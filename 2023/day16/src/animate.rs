@@ -0,0 +1,151 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::beam::{reflect, split, step, Heading};
+
+// Beam fronts at each propagation step; a tile is energized at step k once it has been
+// a front at any step up to k. Fronts that revisit a (tile, heading) state are dropped,
+// so loops end the propagation.
+pub fn propagate(grid: &[Vec<char>], y: usize, x: usize, heading: Heading) -> Vec<Vec<(usize, usize)>> {
+    let mut seen: HashSet<(usize, usize, Heading)> = HashSet::new();
+    let mut fronts = vec![(y, x, heading)];
+    let mut steps = Vec::new();
+
+    while !fronts.is_empty() {
+        fronts.retain(|&state| seen.insert(state));
+        if fronts.is_empty() {
+            break;
+        }
+        steps.push(fronts.iter().map(|&(y, x, _)| (y, x)).collect());
+
+        fronts = fronts.into_iter().flat_map(|(y, x, heading)| {
+            let tile = grid[y][x];
+            let headings = match split(tile, heading) {
+                Some(headings) => headings.to_vec(),
+                None => vec![reflect(tile, heading)],
+            };
+            headings.into_iter()
+                .filter_map(move |heading| step(grid, y, x, heading).map(|(y, x)| (y, x, heading)))
+        }).collect();
+    }
+
+    steps
+}
+
+struct Frame {
+    energized: Vec<Vec<bool>>,
+    fronts: HashSet<(usize, usize)>,
+}
+
+fn frames(grid: &[Vec<char>], steps: &[Vec<(usize, usize)>]) -> Vec<Frame> {
+    let mut energized = vec![vec![false; grid[0].len()]; grid.len()];
+    steps.iter().map(|fronts| {
+        for &(y, x) in fronts {
+            energized[y][x] = true;
+        }
+        Frame{energized: energized.clone(), fronts: fronts.iter().cloned().collect()}
+    }).collect()
+}
+
+const RESET: &str = "\x1b[0m";
+const ENERGIZED_COLOUR: &str = "\x1b[30;43m";
+const FRONT_COLOUR: &str = "\x1b[1;37;41m";
+
+pub fn play(grid: &[Vec<char>], steps: &[Vec<(usize, usize)>], delay: Duration) {
+    let mut stdout = std::io::stdout().lock();
+    for (i, frame) in frames(grid, steps).iter().enumerate() {
+        let mut screen = String::from("\x1b[H\x1b[2J");
+        for (y, line) in grid.iter().enumerate() {
+            for (x, &tile) in line.iter().enumerate() {
+                let paint = if frame.fronts.contains(&(y, x)) {
+                    FRONT_COLOUR
+                } else if frame.energized[y][x] {
+                    ENERGIZED_COLOUR
+                } else {
+                    ""
+                };
+                if paint.is_empty() {
+                    screen.push(tile);
+                } else {
+                    screen.push_str(paint);
+                    screen.push(tile);
+                    screen.push_str(RESET);
+                }
+            }
+            screen.push('\n');
+        }
+        let energized: usize = frame.energized.iter().flatten().filter(|&&e| e).count();
+        screen.push_str(&format!("step {}/{}, energized: {}\n", i + 1, steps.len(), energized));
+
+        stdout.write_all(screen.as_bytes()).unwrap();
+        stdout.flush().unwrap();
+        std::thread::sleep(delay);
+    }
+}
+
+const PIXELS_PER_TILE: usize = 4;
+
+fn tile_colour(tile: char, energized: bool, front: bool) -> [u8; 3] {
+    match (front, energized, tile) {
+        (true, _, _) => [230, 40, 40],
+        (_, true, '.') => [250, 200, 40],
+        (_, true, _) => [255, 240, 160],
+        (_, false, '.') => [20, 20, 30],
+        (_, false, _) => [120, 120, 140],
+    }
+}
+
+fn ppm(grid: &[Vec<char>], frame: &Frame) -> Vec<u8> {
+    let width = grid[0].len() * PIXELS_PER_TILE;
+    let height = grid.len() * PIXELS_PER_TILE;
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for (y, line) in grid.iter().enumerate() {
+        let row: Vec<u8> = line.iter().enumerate()
+            .flat_map(|(x, &tile)| {
+                let colour = tile_colour(tile, frame.energized[y][x], frame.fronts.contains(&(y, x)));
+                colour.repeat(PIXELS_PER_TILE)
+            })
+            .collect();
+        for _ in 0..PIXELS_PER_TILE {
+            image.extend_from_slice(&row);
+        }
+    }
+    image
+}
+
+// Writes frame_00000.ppm, frame_00001.ppm, ... into the output directory.
+pub fn write_ppm_frames(grid: &[Vec<char>], steps: &[Vec<(usize, usize)>], output_dir: &Path) -> std::io::Result<usize> {
+    std::fs::create_dir_all(output_dir)?;
+    let frames = frames(grid, steps);
+    for (i, frame) in frames.iter().enumerate() {
+        std::fs::write(output_dir.join(format!("frame_{:05}.ppm", i)), ppm(grid, frame))?;
+    }
+    Ok(frames.len())
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_propagation_frames() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+        let steps = propagate(&grid, 0, 0, Heading::E);
+        let frames = frames(&grid, &steps);
+
+        assert_eq!(steps[0], vec![(0, 0)]);
+        assert_eq!(steps[1], vec![(0, 1)]);
+        assert_eq!(steps[2].len(), 1);
+        assert_eq!(frames.last().unwrap().energized.iter().flatten().filter(|&&e| e).count(), 46);
+
+        let image = ppm(&grid, &frames[0]);
+        let header = "P6\n40 40\n255\n";
+        assert!(image.starts_with(header.as_bytes()));
+        assert_eq!(image.len(), header.len() + 40 * 40 * 3);
+        assert_eq!(image[header.len()..header.len() + 3], [230, 40, 40]);
+    }
+}
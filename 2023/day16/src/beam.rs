@@ -0,0 +1,262 @@
+use std::collections::HashSet;
+
+use common::graph::strongly_connected_components;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Heading {
+    N, E, S, W
+}
+
+pub fn step(grid: &[Vec<char>], y: usize, x: usize, heading: Heading) -> Option<(usize, usize)> {
+    let next = match heading {
+        Heading::N => (y.checked_sub(1)?, x),
+        Heading::E => (y, x + 1),
+        Heading::S => (y + 1, x),
+        Heading::W => (y, x.checked_sub(1)?),
+    };
+    if next.0 < grid.len() && next.1 < grid[0].len() { Some(next) } else { None }
+}
+
+pub fn reflect(tile: char, heading: Heading) -> Heading {
+    match (tile, heading) {
+        ('\\', Heading::N) => Heading::W,
+        ('\\', Heading::E) => Heading::S,
+        ('\\', Heading::S) => Heading::E,
+        ('\\', Heading::W) => Heading::N,
+
+        ('/', Heading::N) => Heading::E,
+        ('/', Heading::E) => Heading::N,
+        ('/', Heading::S) => Heading::W,
+        ('/', Heading::W) => Heading::S,
+
+        _ => heading,
+    }
+}
+
+// Headings leaving a splitter hit side-on, or None if the beam passes straight through.
+pub fn split(tile: char, heading: Heading) -> Option<[Heading; 2]> {
+    match (tile, heading) {
+        ('-', Heading::N | Heading::S) => Some([Heading::W, Heading::E]),
+        ('|', Heading::E | Heading::W) => Some([Heading::N, Heading::S]),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TileSet {
+    cols: usize,
+    bits: Vec<u64>,
+}
+
+impl TileSet {
+    fn new(rows: usize, cols: usize) -> Self {
+        Self{cols, bits: vec![0; (rows * cols).div_ceil(64)]}
+    }
+
+    fn insert(&mut self, y: usize, x: usize) {
+        let i = y * self.cols + x;
+        self.bits[i / 64] |= 1 << (i % 64);
+    }
+
+    fn union(&mut self, other: &TileSet) {
+        for (word, other) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *word |= other;
+        }
+    }
+
+    pub fn contains(&self, y: usize, x: usize) -> bool {
+        let i = y * self.cols + x;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+// Splitters hit side-on are the only places where beams branch, so the contraption is
+// precomputed as a graph of such splitters joined by the straight-and-mirror segments
+// between them. Energized tiles are then shared per strongly connected component.
+pub struct BeamGraph<'a> {
+    grid: &'a [Vec<char>],
+    splitters: Vec<Vec<Option<usize>>>,
+    component: Vec<usize>,
+    component_tiles: Vec<TileSet>,
+}
+
+struct Segment {
+    tiles: TileSet,
+    target: Option<usize>,
+}
+
+impl<'a> BeamGraph<'a> {
+    pub fn new(grid: &'a [Vec<char>]) -> Self {
+        let mut splitters = vec![vec![None; grid[0].len()]; grid.len()];
+        let mut positions = Vec::new();
+        for (y, line) in grid.iter().enumerate() {
+            for (x, &tile) in line.iter().enumerate() {
+                if tile == '-' || tile == '|' {
+                    splitters[y][x] = Some(positions.len());
+                    positions.push((y, x));
+                }
+            }
+        }
+
+        let mut graph = Self{grid, splitters, component: Vec::new(), component_tiles: Vec::new()};
+
+        let mut own_tiles = Vec::new();
+        let mut edges = Vec::new();
+        for &(y, x) in positions.iter() {
+            let mut tiles = graph.empty();
+            tiles.insert(y, x);
+            let mut targets = Vec::new();
+            let headings = if grid[y][x] == '-' { [Heading::W, Heading::E] } else { [Heading::N, Heading::S] };
+            for heading in headings {
+                if let Some((y, x)) = step(grid, y, x, heading) {
+                    let segment = graph.follow(y, x, heading);
+                    tiles.union(&segment.tiles);
+                    targets.extend(segment.target);
+                }
+            }
+            own_tiles.push(tiles);
+            edges.push(targets);
+        }
+
+        let components = strongly_connected_components(&edges);
+        graph.component = vec![0; positions.len()];
+        for (c, members) in components.iter().enumerate() {
+            for &node in members {
+                graph.component[node] = c;
+            }
+        }
+
+        // Tarjan emits components in reverse topological order, so every successor
+        // component has been filled in by the time it is needed.
+        for members in components.iter() {
+            let mut tiles = graph.empty();
+            for &node in members {
+                tiles.union(&own_tiles[node]);
+                for &target in edges[node].iter() {
+                    if graph.component[target] < graph.component_tiles.len() {
+                        tiles.union(&graph.component_tiles[graph.component[target]]);
+                    }
+                }
+            }
+            graph.component_tiles.push(tiles);
+        }
+
+        graph
+    }
+
+    fn empty(&self) -> TileSet {
+        TileSet::new(self.grid.len(), self.grid[0].len())
+    }
+
+    // Follows a beam entering (y, x) until it hits a splitter side-on, leaves the grid
+    // or starts repeating itself.
+    fn follow(&self, mut y: usize, mut x: usize, mut heading: Heading) -> Segment {
+        let mut tiles = self.empty();
+        let mut seen: HashSet<(usize, usize, Heading)> = HashSet::new();
+
+        loop {
+            tiles.insert(y, x);
+            let tile = self.grid[y][x];
+            if split(tile, heading).is_some() {
+                return Segment{tiles, target: self.splitters[y][x]};
+            }
+            if !seen.insert((y, x, heading)) {
+                return Segment{tiles, target: None};
+            }
+            heading = reflect(tile, heading);
+            match step(self.grid, y, x, heading) {
+                Some(next) => (y, x) = next,
+                None => return Segment{tiles, target: None},
+            }
+        }
+    }
+
+    pub fn energized(&self, y: usize, x: usize, heading: Heading) -> TileSet {
+        let mut segment = self.follow(y, x, heading);
+        if let Some(node) = segment.target {
+            segment.tiles.union(&self.component_tiles[self.component[node]]);
+        }
+        segment.tiles
+    }
+
+    // Every beam that can enter the grid from its edges.
+    pub fn entries(&self) -> Vec<(usize, usize, Heading)> {
+        let rows = self.grid.len();
+        let cols = self.grid[0].len();
+        let mut entries = Vec::new();
+        for y in 0..rows {
+            entries.push((y, 0, Heading::E));
+            entries.push((y, cols - 1, Heading::W));
+        }
+        for x in 0..cols {
+            entries.push((0, x, Heading::S));
+            entries.push((rows - 1, x, Heading::N));
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn tiles(grid: &[Vec<char>], set: &TileSet) -> HashSet<(usize, usize)> {
+        (0..grid.len())
+            .flat_map(|y| (0..grid[0].len()).map(move |x| (y, x)))
+            .filter(|&(y, x)| set.contains(y, x))
+            .collect()
+    }
+
+    // Plain flood over (tile, heading) states, as a reference for the graph.
+    fn flood(grid: &[Vec<char>], y: usize, x: usize, heading: Heading) -> HashSet<(usize, usize)> {
+        let mut seen: HashSet<(usize, usize, Heading)> = HashSet::new();
+        let mut beams = vec![(y, x, heading)];
+        while let Some((y, x, heading)) = beams.pop() {
+            if !seen.insert((y, x, heading)) {
+                continue;
+            }
+            let tile = grid[y][x];
+            let headings = match split(tile, heading) {
+                Some(headings) => headings.to_vec(),
+                None => vec![reflect(tile, heading)],
+            };
+            for heading in headings {
+                if let Some((y, x)) = step(grid, y, x, heading) {
+                    beams.push((y, x, heading));
+                }
+            }
+        }
+        seen.into_iter().map(|(y, x, _)| (y, x)).collect()
+    }
+
+    #[test]
+    fn test_all_entries_match_flood() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+        let graph = BeamGraph::new(&grid);
+
+        for (y, x, heading) in graph.entries() {
+            let tiles = tiles(&grid, &graph.energized(y, x, heading));
+            assert_eq!(tiles, flood(&grid, y, x, heading), "entry {:?}", (y, x, heading));
+        }
+        assert_eq!(graph.energized(0, 0, Heading::E).len(), 46);
+        assert!(graph.energized(0, 3, Heading::S).contains(7, 1));
+    }
+
+    #[test]
+    fn test_splitter_cycle() {
+        let grid: Vec<Vec<char>> = [".|..\\", ".....", ".\\..-", "....."]
+            .iter().map(|l| l.chars().collect()).collect();
+        let graph = BeamGraph::new(&grid);
+
+        for (y, x, heading) in graph.entries() {
+            let tiles = tiles(&grid, &graph.energized(y, x, heading));
+            assert_eq!(tiles, flood(&grid, y, x, heading), "entry {:?}", (y, x, heading));
+        }
+    }
+}
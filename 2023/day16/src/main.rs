@@ -1,100 +1,202 @@
-use std::cmp::max;
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::Duration;
 
-enum Heading {
-    N, E, S, W
+mod animate;
+mod beam;
+
+use beam::{BeamGraph, Heading};
+
+fn solution(input_file_path: &str) -> (usize, usize) {
+    let data = std::fs::read_to_string(input_file_path).unwrap();
+    let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+    let graph = BeamGraph::new(&grid);
+
+    let part_a = graph.energized(0, 0, Heading::E).len();
+    let part_b = graph.entries()
+        .into_iter()
+        .map(|(y, x, heading)| graph.energized(y, x, heading).len())
+        .max()
+        .unwrap();
+
+    (part_a, part_b)
 }
 
-struct TracerState {
-    y: isize,
-    x: isize,
-    heading: Heading,
+fn parse_entry(value: &str) -> Result<(usize, usize, Heading), String> {
+    let tokens: Vec<&str> = value.split(',').collect();
+    let [y, x, heading] = tokens[..] else {
+        return Err(format!("expected 'y,x,heading', got '{}'", value));
+    };
+    let y = y.parse().map_err(|_| format!("invalid row '{}'", y))?;
+    let x = x.parse().map_err(|_| format!("invalid column '{}'", x))?;
+    let heading = match heading {
+        "N" => Heading::N,
+        "E" => Heading::E,
+        "S" => Heading::S,
+        "W" => Heading::W,
+        _ => return Err(format!("invalid heading '{}'", heading)),
+    };
+    Ok((y, x, heading))
 }
 
-fn trace(grid: &mut Vec<Vec<char>>, y: usize, x: usize, heading: Heading) -> usize {
-    let mut tracers: Vec<TracerState> = Vec::new();
-    let mut visited: Vec<Vec<char>> = (0..grid.len()).map(|_| {std::iter::repeat('.').take(grid[0].len()).collect()}).collect();
+fn read_grid(input_file_path: &str, entry: (usize, usize, Heading)) -> Result<Vec<Vec<char>>, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+    if grid.is_empty() || entry.0 >= grid.len() || entry.1 >= grid[0].len() {
+        return Err(format!("entry {:?} is outside the grid", entry));
+    }
+    Ok(grid)
+}
 
-    tracers.push(TracerState{y: y as isize, x: x as isize, heading});
+// Prints the tiles energized by a single beam entering at the given tile.
+fn run_entry(entry: (usize, usize, Heading), input_file_path: &str) -> Result<(), String> {
+    let grid = read_grid(input_file_path, entry)?;
 
-    while !tracers.is_empty() {
-        match (grid[tracers[0].y as usize][tracers[0].x as usize], &tracers[0].heading) {
-            ('\\', Heading::N) => tracers[0].heading = Heading::W,
-            ('\\', Heading::E) => tracers[0].heading = Heading::S,
-            ('\\', Heading::S) => tracers[0].heading = Heading::E,
-            ('\\', Heading::W) => tracers[0].heading = Heading::N,
+    let tiles = BeamGraph::new(&grid).energized(entry.0, entry.1, entry.2);
+    for y in 0..grid.len() {
+        let line: String = (0..grid[0].len()).map(|x| if tiles.contains(y, x) { '#' } else { '.' }).collect();
+        println!("{}", line);
+    }
+    println!("energized: {}", tiles.len());
 
-            ('/', Heading::N) => tracers[0].heading = Heading::E,
-            ('/', Heading::E) => tracers[0].heading = Heading::N,
-            ('/', Heading::S) => tracers[0].heading = Heading::W,
-            ('/', Heading::W) => tracers[0].heading = Heading::S,
+    Ok(())
+}
 
-            ('-', Heading::N | Heading::S) => {
-                tracers.push(TracerState{x: tracers[0].x, y: tracers[0].y, heading: Heading::E});
-                tracers[0].heading = Heading::W;
-            },
+// Plays the propagation in the terminal, or writes it as PPM frames when given a directory.
+fn run_animate(entry: (usize, usize, Heading), input_file_path: &str, options: &[String]) -> Result<(), String> {
+    let grid = read_grid(input_file_path, entry)?;
+    let mut ppm_dir = None;
+    let mut delay = 50u64;
+    for option in options.chunks(2) {
+        match option {
+            [flag, dir] if flag == "--ppm" => ppm_dir = Some(dir),
+            [flag, ms] if flag == "--delay" => delay = ms.parse().map_err(|_| format!("invalid --delay '{}'", ms))?,
+            _ => return Err(format!("unknown option '{}'", option.join(" "))),
+        }
+    }
 
-            ('|', Heading::E | Heading::W) => {
-                tracers.push(TracerState{x: tracers[0].x, y: tracers[0].y, heading: Heading::S});
-                tracers[0].heading = Heading::N;
-            },
+    let steps = animate::propagate(&grid, entry.0, entry.1, entry.2);
+    match ppm_dir {
+        Some(dir) => {
+            let count = animate::write_ppm_frames(&grid, &steps, Path::new(dir))
+                .map_err(|e| format!("cannot write frames to '{}': {}", dir, e))?;
+            println!("wrote {} frames to {}", count, dir);
+        },
+        None => animate::play(&grid, &steps, Duration::from_millis(delay)),
+    }
+
+    Ok(())
+}
+
+// Usage: day16 --entry <y>,<x>,<N|E|S|W> <input>
+//        day16 --animate <y>,<x>,<N|E|S|W> <input> [--ppm <dir>] [--delay <ms>]
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, entry, path] if flag == "--entry" => run_entry(parse_entry(entry)?, path),
+        [flag, entry, path, options @ ..] if flag == "--animate" => run_animate(parse_entry(entry)?, path, options),
+        _ => Err("expected --entry or --animate <y>,<x>,<heading> <input>".to_string()),
+    }
+}
 
-            _ => (),
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
         }
+        return ExitCode::SUCCESS;
+    }
 
-        tracers.retain(|s| {
-            match (&s.heading, visited[s.y as usize][s.x as usize]) {
-                (Heading::N, '^') => false,
-                (Heading::E, '>') => false,
-                (Heading::S, 'v') => false,
-                (Heading::W, '<') => false,
-                _ => true,
-            }
-        });
+    println!("{:?}", solution("day16/input/test.txt"));
+    println!("{:?}", solution("day16/input/input.txt"));
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod unit_tests {
 
-        if tracers.is_empty() {
-            break;
+    use super::*;
+
+    // The original single-beam tracer, kept as a reference for the beam graph. It marks
+    // each tile with the last heading seen there and drops beams repeating that mark.
+    fn trace(grid: &[Vec<char>], y: usize, x: usize, heading: Heading) -> usize {
+        struct TracerState {
+            y: isize,
+            x: isize,
+            heading: Heading,
         }
 
-        visited[tracers[0].y as usize][tracers[0].x as usize] = match tracers[0].heading {
-            Heading::N => '^',
-            Heading::E => '>',
-            Heading::S => 'v',
-            Heading::W => '<',
-        };
-
-        match tracers[0].heading {
-            Heading::N => tracers[0].y -= 1,
-            Heading::E => tracers[0].x += 1,
-            Heading::S => tracers[0].y += 1,
-            Heading::W => tracers[0].x -= 1,
+        let mut tracers: Vec<TracerState> = vec![TracerState{y: y as isize, x: x as isize, heading}];
+        let mut visited: Vec<Vec<char>> = vec![vec!['.'; grid[0].len()]; grid.len()];
+
+        while !tracers.is_empty() {
+            match (grid[tracers[0].y as usize][tracers[0].x as usize], tracers[0].heading) {
+                ('\\', Heading::N) => tracers[0].heading = Heading::W,
+                ('\\', Heading::E) => tracers[0].heading = Heading::S,
+                ('\\', Heading::S) => tracers[0].heading = Heading::E,
+                ('\\', Heading::W) => tracers[0].heading = Heading::N,
+
+                ('/', Heading::N) => tracers[0].heading = Heading::E,
+                ('/', Heading::E) => tracers[0].heading = Heading::N,
+                ('/', Heading::S) => tracers[0].heading = Heading::W,
+                ('/', Heading::W) => tracers[0].heading = Heading::S,
+
+                ('-', Heading::N | Heading::S) => {
+                    tracers.push(TracerState{x: tracers[0].x, y: tracers[0].y, heading: Heading::E});
+                    tracers[0].heading = Heading::W;
+                },
+
+                ('|', Heading::E | Heading::W) => {
+                    tracers.push(TracerState{x: tracers[0].x, y: tracers[0].y, heading: Heading::S});
+                    tracers[0].heading = Heading::N;
+                },
+
+                _ => (),
+            }
+
+            tracers.retain(|s| {
+                !matches!((s.heading, visited[s.y as usize][s.x as usize]),
+                          (Heading::N, '^') | (Heading::E, '>') | (Heading::S, 'v') | (Heading::W, '<'))
+            });
+
+            if tracers.is_empty() {
+                break;
+            }
+
+            visited[tracers[0].y as usize][tracers[0].x as usize] = match tracers[0].heading {
+                Heading::N => '^',
+                Heading::E => '>',
+                Heading::S => 'v',
+                Heading::W => '<',
+            };
+
+            match tracers[0].heading {
+                Heading::N => tracers[0].y -= 1,
+                Heading::E => tracers[0].x += 1,
+                Heading::S => tracers[0].y += 1,
+                Heading::W => tracers[0].x -= 1,
+            }
+
+            tracers.retain(|s| s.y >= 0 && s.y < grid.len() as isize && s.x >= 0 && s.x < grid[0].len() as isize);
         }
 
-        tracers.retain(|s| {
-            s.y >= 0 &&
-            s.y < grid.len() as isize &&
-            s.x >= 0 &&
-            s.x < grid[0].len() as isize
-        });
+        visited.into_iter().flatten().filter(|&c| c != '.').count()
     }
 
-    visited.into_iter().flatten().filter(|&c| c != '.').count()
-}
-
-fn solution(input_file_path: &str) -> (usize, usize) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
-    assert!(grid.len() == grid[0].len());
-    let size = grid.len();
-    let part_a = trace(&mut grid, 0, 0, Heading::E);
-    let part_b = max(
-        (0..size).map(|row| max(trace(&mut grid, row, 0, Heading::E), trace(&mut grid, row, size - 1, Heading::W))).max().unwrap(),
-        (0..size).map(|col| max(trace(&mut grid, 0, col, Heading::S), trace(&mut grid, size - 1, col, Heading::N))).max().unwrap()
-    );
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), (46, 51));
+    }
 
-    (part_a, part_b)
-}
+    #[test]
+    fn test_graph_matches_trace() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let grid: Vec<Vec<char>> = data.lines().map(|l| l.chars().collect()).collect();
+        let graph = BeamGraph::new(&grid);
 
-fn main() {
-    println!("{:?}", solution("day16/input/test.txt"));
-    println!("{:?}", solution("day16/input/input.txt"));
+        for (y, x, heading) in graph.entries() {
+            assert_eq!(graph.energized(y, x, heading).len(), trace(&grid, y, x, heading), "entry {:?}", (y, x, heading));
+        }
+    }
 }
@@ -0,0 +1,105 @@
+// Reference implementation using a hashed distance table and a binary heap, kept to
+// cross-check and benchmark the dense solver. Run the benchmark with:
+//   cargo test --release -p day17 -- --ignored --nocapture
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Instant;
+
+use common::random::Lcg;
+
+use super::*;
+
+#[derive(Eq, PartialEq)]
+struct State {
+    tracer: Tracer,
+    cost: u64,
+}
+
+impl State {
+    fn new(tracer: Tracer, cost: u64) -> Self {
+        Self{tracer, cost}
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost)
+             .then_with(|| self.tracer.vector.position.cmp(&other.tracer.vector.position))
+    }
+}
+
+pub fn solve_hashed(grid: &Vec<Vec<u8>>, rules: &CrucibleRules) -> u64 {
+    let mut candidates = BinaryHeap::new();
+    let mut distances: HashMap<Tracer, u64> = HashMap::new();
+
+    let goal = rules.goal(grid);
+    let init = start_tracers(rules);
+
+    for tracer in init {
+        candidates.push(State::new(tracer, 0));
+        distances.insert(tracer, 0);
+    }
+
+    while let Some(State{tracer, cost}) = candidates.pop() {
+        if rules.is_end(tracer, goal) {
+           return cost;
+        }
+
+        let current_cost = *distances.get(&tracer).unwrap_or(&u64::MAX);
+        if cost > current_cost {
+            continue
+        }
+
+        let neighbors = filter_outside_grid(rules.adjacent(tracer), grid);
+
+        for neighbor in neighbors {
+            let neighbor_cost = get_val(grid, &neighbor.vector.position).unwrap();
+            let next_state = State::new(neighbor, cost + neighbor_cost);
+            if next_state.cost < *distances.get(&next_state.tracer).unwrap_or(&u64::MAX) {
+                *distances.entry(next_state.tracer).or_insert(u64::MAX) = next_state.cost;
+                candidates.push(next_state);
+            }
+        }
+    }
+
+    u64::MAX
+}
+
+fn generate_grid(rows: usize, cols: usize, seed: u64) -> Vec<Vec<u8>> {
+    let mut lcg = Lcg::new(seed);
+    (0..rows).map(|_| (0..cols).map(|_| (lcg.below(9) + 1) as u8).collect()).collect()
+}
+
+#[test]
+fn test_matches_hashed_solver() {
+    let grid = generate_grid(40, 30, 17);
+    for rules in [CrucibleRules::part_1(), CrucibleRules::part_2(),
+                  CrucibleRules{allow_reverse: true, start: (20, 15), ..CrucibleRules::part_2()}] {
+        assert_eq!(solve(&grid, &rules), solve_hashed(&grid, &rules));
+    }
+}
+
+#[test]
+#[ignore]
+fn bench_solve() {
+    let grid = generate_grid(141, 141, 2023);
+    for rules in [CrucibleRules::part_1(), CrucibleRules::part_2()] {
+        let start = Instant::now();
+        let hashed = solve_hashed(&grid, &rules);
+        let hashed_time = start.elapsed();
+
+        let start = Instant::now();
+        let dense = solve(&grid, &rules);
+        let dense_time = start.elapsed();
+
+        assert_eq!(hashed, dense);
+        println!("{}..={}: hashed {:?}, dense {:?} ({:.1}x)", rules.min_straight, rules.max_straight,
+                 hashed_time, dense_time, hashed_time.as_secs_f64() / dense_time.as_secs_f64());
+    }
+}
@@ -1,5 +1,7 @@
-use std::collections::{BinaryHeap, HashMap};
-use std::cmp::Ordering;
+use std::process::ExitCode;
+
+#[cfg(test)]
+mod bench;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 enum Direction {
@@ -85,34 +87,70 @@ impl Tracer {
             steps_forward: 1,
         }
     }
+
+    fn reverse(&self) -> Self {
+        Self {
+            vector: self.vector.turn_cw().turn_cw().step(),
+            steps_forward: 1,
+        }
+    }
 }
 
-#[derive(Eq, PartialEq)]
-struct State {
-    tracer: Tracer,
-    cost: u64,
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct CrucibleRules {
+    min_straight: usize,
+    max_straight: usize,
+    start: (isize, isize),
+    goal: Option<(isize, isize)>,
+    allow_reverse: bool,
 }
 
-impl State {
-    fn new(tracer: Tracer, cost: u64) -> Self {
-        Self{tracer, cost}
+impl CrucibleRules {
+    fn part_1() -> Self {
+        Self{min_straight: 1, max_straight: 3, start: (0, 0), goal: None, allow_reverse: false}
     }
-}
 
-impl PartialOrd for State {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+    fn part_2() -> Self {
+        Self{min_straight: 4, max_straight: 10, ..Self::part_1()}
     }
-}
 
-impl Ord for State {
-    fn cmp(&self, other: &Self) -> Ordering {
-        other.cost.cmp(&self.cost)
-             .then_with(|| self.tracer.vector.position.cmp(&other.tracer.vector.position))
+    fn goal(&self, grid: &[Vec<u8>]) -> (isize, isize) {
+        self.goal.unwrap_or((grid.len() as isize - 1, grid[0].len() as isize - 1))
+    }
+
+    fn validate(&self, grid: &[Vec<u8>]) -> Result<(), String> {
+        if self.max_straight == 0 || self.min_straight > self.max_straight {
+            return Err(format!("invalid straight-line range {}..={}", self.min_straight, self.max_straight));
+        }
+        for (name, pos) in [("start", self.start), ("goal", self.goal(grid))] {
+            if get_val(grid, &pos).is_none() {
+                return Err(format!("{} {:?} is outside the grid", name, pos));
+            }
+        }
+        Ok(())
+    }
+
+    fn adjacent(&self, tracer: Tracer) -> Vec<Tracer> {
+        if tracer.steps_forward < self.min_straight {
+            return vec![tracer.forward()];
+        }
+
+        let mut neighbors = vec![tracer.left(), tracer.right()];
+        if tracer.steps_forward < self.max_straight {
+            neighbors.push(tracer.forward());
+        }
+        if self.allow_reverse {
+            neighbors.push(tracer.reverse());
+        }
+        neighbors
+    }
+
+    fn is_end(&self, tracer: Tracer, goal: (isize, isize)) -> bool {
+        tracer.vector.position == goal && tracer.steps_forward >= self.min_straight
     }
 }
 
-fn get_val<T: Into<u64> + Copy>(grid: &Vec<Vec<T>>, pos: &(isize, isize)) -> Option<u64> {
+fn get_val<T: Into<u64> + Copy>(grid: &[Vec<T>], pos: &(isize, isize)) -> Option<u64> {
     if pos.0 < 0 || pos.0 as usize >= grid.len() || pos.1 < 0 || pos.1 as usize >= grid[0].len() {
         None
     } else {
@@ -120,40 +158,80 @@ fn get_val<T: Into<u64> + Copy>(grid: &Vec<Vec<T>>, pos: &(isize, isize)) -> Opt
     }
 }
 
-fn solve(grid: &Vec<Vec<u8>>, adjacency_fn: &dyn Fn(Tracer) -> Vec<Tracer>, end_condition: &dyn Fn(Tracer) -> bool) -> u64 {
-    let mut candidates = BinaryHeap::new();
-    let mut distances: HashMap<Tracer, u64> = HashMap::new();
+fn start_tracers(rules: &CrucibleRules) -> Vec<Tracer> {
+    [Direction::N, Direction::E, Direction::S, Direction::W]
+        .into_iter()
+        .map(|direction| Tracer::new(rules.start, direction))
+        .collect()
+}
 
-    let init: Vec<Tracer> = vec![Tracer::new((0, 0), Direction::E), Tracer::new((0, 0), Direction::S)];
+// Dense index over (row, col, direction, steps forward), replacing a hashed lookup.
+struct StateSpace {
+    cols: usize,
+    steps: usize,
+    size: usize,
+}
 
-    for tracer in init {
-        candidates.push(State::new(tracer, 0));
-        distances.insert(tracer, 0);
+impl StateSpace {
+    fn new(grid: &[Vec<u8>], rules: &CrucibleRules) -> Self {
+        let steps = rules.max_straight + 1;
+        Self{cols: grid[0].len(), steps, size: grid.len() * grid[0].len() * 4 * steps}
     }
 
-    while let Some(State{tracer, cost}) = candidates.pop() {
-        if end_condition(tracer) {
-           return cost;
-        }
+    fn index(&self, tracer: &Tracer) -> usize {
+        let (row, col) = tracer.vector.position;
+        let cell = row as usize * self.cols + col as usize;
+        (cell * 4 + tracer.vector.direction as usize) * self.steps + tracer.steps_forward
+    }
+}
+
+// Heat losses are single digits, so Dijkstra can use a circular bucket queue (Dial's
+// algorithm) indexed by cost instead of a binary heap.
+const BUCKETS: usize = 10;
+
+fn solve(grid: &Vec<Vec<u8>>, rules: &CrucibleRules) -> u64 {
+    let space = StateSpace::new(grid, rules);
+    let mut distances = vec![u64::MAX; space.size];
+    let mut buckets: Vec<Vec<Tracer>> = vec![Vec::new(); BUCKETS];
+    let mut pending = 0usize;
+
+    let goal = rules.goal(grid);
+
+    for tracer in start_tracers(rules) {
+        distances[space.index(&tracer)] = 0;
+        buckets[0].push(tracer);
+        pending += 1;
+    }
+
+    let mut cost = 0u64;
+    while pending > 0 {
+        let bucket = (cost % BUCKETS as u64) as usize;
+        let Some(tracer) = buckets[bucket].pop() else {
+            cost += 1;
+            continue
+        };
+        pending -= 1;
 
-        let current_cost = *distances.get(&tracer).unwrap_or(&u64::max_value());
-        if cost > current_cost {
+        if distances[space.index(&tracer)] < cost {
             continue
         }
 
-        let neighbors = adjacency_fn(tracer);
+        if rules.is_end(tracer, goal) {
+            return cost;
+        }
 
-        for neighbor in neighbors {
-            let neighbor_cost = get_val(grid, &neighbor.vector.position).unwrap();
-            let next_state = State::new(neighbor, cost + neighbor_cost);
-            if next_state.cost < *distances.get(&next_state.tracer).unwrap_or(&u64::max_value()) {
-                *distances.entry(next_state.tracer).or_insert(u64::max_value()) = next_state.cost;
-                candidates.push(next_state);
+        for neighbor in filter_outside_grid(rules.adjacent(tracer), grid) {
+            let next_cost = cost + get_val(grid, &neighbor.vector.position).unwrap();
+            let next_index = space.index(&neighbor);
+            if next_cost < distances[next_index] {
+                distances[next_index] = next_cost;
+                buckets[(next_cost % BUCKETS as u64) as usize].push(neighbor);
+                pending += 1;
             }
         }
     }
 
-    u64::max_value()
+    u64::MAX
 }
 
 fn filter_outside_grid(mut tracers: Vec<Tracer>, grid: &Vec<Vec<u8>>) -> Vec<Tracer> {
@@ -162,53 +240,98 @@ fn filter_outside_grid(mut tracers: Vec<Tracer>, grid: &Vec<Vec<u8>>) -> Vec<Tra
     tracers
 }
 
-fn solution(input_file_path: &str) -> (u64, u64) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let grid: Vec<Vec<u8>> = data.lines().map(|l| l.chars().map(|c| c.to_digit(10).unwrap() as u8).collect()).collect();
+fn parse_blocks(data: &str) -> Result<Vec<Vec<u8>>, String> {
+    let grid: Vec<Vec<u8>> = data.lines().enumerate().map(|(row, l)| {
+        l.chars().map(|c| c.to_digit(10).map(|d| d as u8).ok_or(format!("row {}: invalid heat loss '{}'", row + 1, c))).collect()
+    }).collect::<Result<_, String>>()?;
 
-    let part_1_adjacency = |tracer: Tracer| -> Vec<Tracer> {
-        let neighbors = if tracer.steps_forward < 3 {
-            vec![tracer.left(), tracer.right(), tracer.forward()]
-        } else {
-            vec![tracer.left(), tracer.right()]
-        };
+    let cols = grid.first().map_or(0, |line| line.len());
+    if cols == 0 {
+        return Err("no blocks".to_string());
+    }
+    if let Some(row) = grid.iter().position(|line| line.len() != cols) {
+        return Err(format!("row {} has {} blocks, expected {}", row + 1, grid[row].len(), cols));
+    }
+    Ok(grid)
+}
 
-        filter_outside_grid(neighbors, &grid)
-    };
-
-    let part_1_end = |tracer: Tracer| -> bool {
-        tracer.vector.position.0 as usize == grid.len() - 1 &&
-        tracer.vector.position.1 as usize == grid[0].len() - 1
-    };
-
-    let part_2_adjacency = |tracer: Tracer| -> Vec<Tracer> {
-        let neighbors = if tracer.steps_forward < 4 {
-            vec![tracer.forward()]
-        } else if tracer.steps_forward >= 4 && tracer.steps_forward < 10 {
-            vec![tracer.left(), tracer.right(), tracer.forward()]
-        } else {
-            vec![tracer.left(), tracer.right()]
-        };
+fn parse_grid(input_file_path: &str) -> Result<Vec<Vec<u8>>, String> {
+    let data = std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))?;
+    parse_blocks(&data).map_err(|e| format!("{}: {}", input_file_path, e))
+}
 
-        filter_outside_grid(neighbors, &grid)
-    };
+fn solution(input_file_path: &str) -> Result<(u64, u64), String> {
+    let grid = parse_grid(input_file_path)?;
 
-    let part_2_end = |tracer: Tracer| -> bool {
-        tracer.vector.position.0 as usize == grid.len() - 1 &&
-        tracer.vector.position.1 as usize == grid[0].len() - 1 &&
-        tracer.steps_forward >= 4
-    };
+    let part_1 = solve(&grid, &CrucibleRules::part_1());
+    let part_2 = solve(&grid, &CrucibleRules::part_2());
+
+    Ok((part_1, part_2))
+}
 
-    let part_1 = solve(&grid, &part_1_adjacency, &part_1_end);
-    let part_2 = solve(&grid, &part_2_adjacency, &part_2_end);
+fn parse_position(value: &str) -> Result<(isize, isize), String> {
+    let (row, col) = value.split_once(',').ok_or(format!("expected 'row,col', got '{}'", value))?;
+    let row = row.trim().parse().map_err(|_| format!("invalid row in '{}'", value))?;
+    let col = col.trim().parse().map_err(|_| format!("invalid column in '{}'", value))?;
+    Ok((row, col))
+}
+
+fn parse_args(args: &[String]) -> Result<(String, CrucibleRules), String> {
+    let mut input_file_path = None;
+    let mut rules = CrucibleRules::part_1();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--allow-reverse" {
+            rules.allow_reverse = true;
+            continue;
+        }
+        let value = args.next().ok_or(format!("missing value for '{}'", arg))?;
+        match arg.as_str() {
+            "--min-straight" => rules.min_straight = value.parse().map_err(|_| format!("invalid --min-straight '{}'", value))?,
+            "--max-straight" => rules.max_straight = value.parse().map_err(|_| format!("invalid --max-straight '{}'", value))?,
+            "--start" => rules.start = parse_position(value)?,
+            "--goal" => rules.goal = Some(parse_position(value)?),
+            "--input" => input_file_path = Some(value.clone()),
+            _ => return Err(format!("unknown argument '{}'", arg)),
+        }
+    }
+
+    Ok((input_file_path.ok_or("missing --input")?, rules))
+}
 
-    (part_1, part_2)
+fn run_variant(args: &[String]) -> Result<u64, String> {
+    let (input_file_path, rules) = parse_args(args)?;
+    let grid = parse_grid(&input_file_path)?;
+    rules.validate(&grid)?;
+    Ok(solve(&grid, &rules))
 }
 
-fn main() {
-    println!("{:?}", solution("day17/input/test.txt"));
-    println!("{:?}", solution("day17/input/test_2.txt"));
-    println!("{:?}", solution("day17/input/input.txt"));
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return match run_variant(&args) {
+            Ok(cost) => {
+                println!("{}", cost);
+                ExitCode::SUCCESS
+            },
+            Err(e) => {
+                eprintln!("Error: {e}");
+                ExitCode::from(1)
+            },
+        };
+    }
+
+    for input_file_path in ["day17/input/test.txt", "day17/input/test_2.txt", "day17/input/input.txt"] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
 }
 
 #[cfg(test)]
@@ -236,4 +359,33 @@ mod unit_tests {
         assert_eq!(tracer.vector.direction, Direction::E);
         assert_eq!(tracer.steps_forward, 4);
     }
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(solution("input/test.txt"), Ok((102, 94)));
+        assert_eq!(solution("input/test_2.txt"), Ok((59, 71)));
+    }
+
+    #[test]
+    fn test_malformed_grids() {
+        assert!(parse_grid("input/missing.txt").unwrap_err().starts_with("cannot read"));
+        assert_eq!(parse_blocks("12\n3x\n"), Err("row 2: invalid heat loss 'x'".to_string()));
+        assert_eq!(parse_blocks("12\n3\n"), Err("row 2 has 1 blocks, expected 2".to_string()));
+        assert!(parse_blocks("").is_err());
+    }
+
+    #[test]
+    fn test_crucible_rules() {
+        let grid = parse_grid("input/test.txt").unwrap();
+
+        assert_eq!(solve(&grid, &CrucibleRules::part_1()), 102);
+        assert_eq!(solve(&grid, &CrucibleRules::part_2()), 94);
+
+        let rules = CrucibleRules{start: (12, 12), goal: Some((0, 0)), ..CrucibleRules::part_1()};
+        assert_eq!(solve(&grid, &rules), solve(&grid, &CrucibleRules::part_1()) + 2 - 3);
+
+        let grid = parse_grid("input/test_2.txt").unwrap();
+        let rules = CrucibleRules{allow_reverse: true, ..CrucibleRules::part_2()};
+        assert!(solve(&grid, &rules) <= solve(&grid, &CrucibleRules::part_2()));
+    }
 }
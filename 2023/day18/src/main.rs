@@ -1,5 +1,11 @@
+use std::process::ExitCode;
 use std::str::FromStr;
 
+mod polygon;
+mod svg;
+
+use polygon::{Point, Polygon};
+
 #[derive(Debug)]
 enum Direction {
     U {steps: u64},
@@ -8,64 +14,235 @@ enum Direction {
     L {steps: u64},
 }
 
-fn solve(dig_plan: Vec<Direction>) -> u64 {
-    let mut pos = (0i64, 0i64);
-    let mut next;
-    let mut area = 0i64;
-    let mut perim = 0u64;
-    for dir in dig_plan {
-        match dir {
-            Direction::U{steps} => { next = (pos.0 - steps as i64, pos.1); perim += steps },
-            Direction::R{steps} => { next = (pos.0, pos.1 + steps as i64); perim += steps },
-            Direction::D{steps} => { next = (pos.0 + steps as i64, pos.1); perim += steps },
-            Direction::L{steps} => { next = (pos.0, pos.1 - steps as i64); perim += steps },
-        };
-        area += pos.0 * next.1;
-        area -= pos.1 * next.0;
-        pos = next;
+impl Direction {
+    fn delta(&self) -> (Point, u64) {
+        match *self {
+            Direction::U{steps} => ((-1,  0), steps),
+            Direction::R{steps} => (( 0,  1), steps),
+            Direction::D{steps} => (( 1,  0), steps),
+            Direction::L{steps} => (( 0, -1), steps),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Segment {
+    direction: Direction,
+    colour: String,
+}
+
+impl Segment {
+    fn new(direction: Direction, colour: &str) -> Self {
+        Self{direction, colour: colour.to_string()}
+    }
+}
+
+fn parse_colour(token: &str) -> Result<&str, String> {
+    token.strip_prefix('(').and_then(|t| t.strip_suffix(')'))
+        .filter(|colour| svg::is_hex_colour(colour))
+        .ok_or(format!("invalid colour '{}', expected (#rrggbb)", token))
+}
+
+fn trace(dig_plan: &[Segment]) -> Polygon {
+    Polygon::trace(dig_plan.iter().map(|segment| segment.direction.delta()))
+}
+
+fn solve(dig_plan: Vec<Segment>) -> Result<u64, String> {
+    let trench = trace(&dig_plan);
+    if !trench.is_closed() {
+        return Err("dig plan does not end where it started".to_string());
+    }
+    if let Some((i, j)) = trench.self_intersections().first() {
+        return Err(format!("dig plan crosses itself at steps {} and {}", i + 1, j + 1));
     }
-    (area.abs() as u64 + perim) / 2 + 1
-}
-
-fn parse_data_part1(data: &String) -> Vec<Direction> {
-    data.lines().map(|line| {
-        let tokens: Vec<_> = line.split(' ').collect();
-        let direction = match (tokens[0].chars().nth(0).unwrap(), u64::from_str(tokens[1]).unwrap()) {
-            ('U', steps) => Direction::U{steps},
-            ('R', steps) => Direction::R{steps},
-            ('D', steps) => Direction::D{steps},
-            ('L', steps) => Direction::L{steps},
-            _ => unreachable!(),
+    trench.lattice_points().ok_or("dig plan does not enclose a simple loop".to_string())
+}
+
+// Splits a "<direction> <steps> (#rrggbb)" line into its three tokens.
+fn split_line(line: &str) -> Result<[&str; 3], String> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    <[&str; 3]>::try_from(tokens).map_err(|_| format!("expected '<direction> <steps> (#rrggbb)', got '{}'", line))
+}
+
+fn parse_data_part1(data: &str) -> Result<Vec<Segment>, String> {
+    data.lines().enumerate().map(|(n, line)| {
+        let [direction, steps, colour] = split_line(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        let steps = u64::from_str(steps).map_err(|_| format!("line {}: invalid step count '{}'", n + 1, steps))?;
+        let direction = match direction {
+            "U" => Direction::U{steps},
+            "R" => Direction::R{steps},
+            "D" => Direction::D{steps},
+            "L" => Direction::L{steps},
+            _ => return Err(format!("line {}: invalid direction '{}'", n + 1, direction)),
         };
-        direction
+        Ok(Segment::new(direction, parse_colour(colour).map_err(|e| format!("line {}: {}", n + 1, e))?))
     }).collect()
 }
 
-fn parse_data_part2(data: &String) -> Vec<Direction> {
-    data.lines().map(|line| {
-        let tokens: Vec<_> = line.split(' ').collect();
-        let steps = u64::from_str_radix(&tokens[2][2..7], 16).unwrap();
-        match tokens[2][7..8].parse().unwrap() {
-            0 => Direction::R{steps},
-            1 => Direction::D{steps},
-            2 => Direction::L{steps},
-            3 => Direction::U{steps},
-            _ => unreachable!(),
-        }
+// The colour's first five hex digits are the step count and the last one the direction.
+fn parse_data_part2(data: &str) -> Result<Vec<Segment>, String> {
+    data.lines().enumerate().map(|(n, line)| {
+        let [_, _, colour] = split_line(line).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        let colour = parse_colour(colour).map_err(|e| format!("line {}: {}", n + 1, e))?;
+        let steps = u64::from_str_radix(&colour[1..6], 16).unwrap();
+        let direction = match &colour[6..7] {
+            "0" => Direction::R{steps},
+            "1" => Direction::D{steps},
+            "2" => Direction::L{steps},
+            "3" => Direction::U{steps},
+            d => return Err(format!("line {}: invalid direction digit '{}' in {}", n + 1, d, colour)),
+        };
+        Ok(Segment::new(direction, colour))
     }).collect()
 }
 
-fn solution(input_file_path: &str) -> (u64, u64) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
+fn solution(input_file_path: &str) -> Result<(u64, u64), String> {
+    let data = read_input(input_file_path)?;
 
-    let part_1 = solve(parse_data_part1(&data));
-    let part_2 = solve(parse_data_part2(&data));
+    let part_1 = parse_data_part1(&data).and_then(solve).map_err(|e| format!("part 1: {}", e))?;
+    let part_2 = parse_data_part2(&data).and_then(solve).map_err(|e| format!("part 2: {}", e))?;
 
-    (part_1, part_2)
+    Ok((part_1, part_2))
 }
 
-fn main() {
-    println!("{:?}", solution("day18/input/test.txt"));
-    println!("{:?}", solution("day18/input/input.txt"));
+const MAX_DRAW_CELLS: u64 = 40_000;
+
+fn read_input(input_file_path: &str) -> Result<String, String> {
+    std::fs::read_to_string(input_file_path).map_err(|e| format!("cannot read '{}': {}", input_file_path, e))
+}
+
+// Rasterizes the part 1 plan so it can be compared against the puzzle's drawing.
+fn run_draw(input_file_path: &str) -> Result<(), String> {
+    let trench = trace(&parse_data_part1(&read_input(input_file_path)?)?);
+
+    let grid = trench.rasterize(MAX_DRAW_CELLS)
+        .ok_or(format!("bounding box {:?} is too large to draw", trench.bounding_box()))?;
+    print!("{}", grid);
+    println!("bounding box: {:?}, boundary: {}, interior: {}",
+             trench.bounding_box(), trench.boundary_points(),
+             trench.interior_points().map_or("not a simple loop".to_string(), |n| n.to_string()));
+
+    Ok(())
 }
 
+// Writes <prefix>_part1.svg and <prefix>_part2.svg with each edge in its plan colour.
+fn run_svg(input_file_path: &str, prefix: &str) -> Result<(), String> {
+    let data = read_input(input_file_path)?;
+
+    for (part, dig_plan) in [(1, parse_data_part1(&data)?), (2, parse_data_part2(&data)?)] {
+        let trench = trace(&dig_plan);
+        let colours: Vec<String> = dig_plan.into_iter().map(|segment| segment.colour).collect();
+        let path = format!("{}_part{}.svg", prefix, part);
+        std::fs::write(&path, svg::render(trench.vertices(), &colours))
+            .map_err(|e| format!("cannot write '{}': {}", path, e))?;
+        println!("wrote {}", path);
+    }
+
+    Ok(())
+}
+
+// Usage: day18 --draw <input>
+//        day18 --svg <input> --output <prefix>
+fn run(args: &[String]) -> Result<(), String> {
+    match args {
+        [flag, path] if flag == "--draw" => run_draw(path),
+        [flag, path, output, prefix] if flag == "--svg" && output == "--output" => run_svg(path, prefix),
+        _ => Err("expected --draw <input> or --svg <input> --output <prefix>".to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(e) = run(&args) {
+            eprintln!("Error: {e}");
+            return ExitCode::from(1);
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    for input_file_path in ["day18/input/test.txt", "day18/input/input.txt"] {
+        match solution(input_file_path) {
+            Ok(result) => println!("{:?}", result),
+            Err(e) => {
+                eprintln!("Error: {e}");
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_rasterize_sample() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let trench = trace(&parse_data_part1(&data).unwrap());
+
+        let expected = "#######\n\
+                        #######\n\
+                        #######\n\
+                        ..#####\n\
+                        ..#####\n\
+                        #######\n\
+                        #####..\n\
+                        #######\n\
+                        .######\n\
+                        .######\n";
+
+        assert_eq!(trench.bounding_box(), ((0, 0), (9, 6)));
+        assert_eq!(trench.boundary_points(), 38);
+        assert_eq!(trench.interior_points(), Some(24));
+        assert_eq!(trench.rasterize(100).unwrap(), expected);
+        assert_eq!(Some(expected.matches('#').count() as u64), trench.lattice_points());
+    }
+
+    #[test]
+    fn test_rejects_degenerate_plans() {
+        assert_eq!(solution("input/test.txt"), Ok((62, 952408144115)));
+
+        let there_and_back = parse_data_part1("R 3 (#000000)\nL 3 (#000000)\n").unwrap();
+        assert!(solve(there_and_back).is_err());
+
+        let open = parse_data_part1("R 3 (#000000)\nD 3 (#000000)\n").unwrap();
+        assert_eq!(solve(open), Err("dig plan does not end where it started".to_string()));
+    }
+
+    #[test]
+    fn test_colours_and_svg() {
+        let data = std::fs::read_to_string("input/test.txt").unwrap();
+        let dig_plan = parse_data_part1(&data).unwrap();
+
+        assert_eq!(dig_plan[0].colour, "#70c710");
+        assert_eq!(dig_plan[13].colour, "#7a21e3");
+
+        let trench = trace(&dig_plan);
+        let colours: Vec<String> = dig_plan.into_iter().map(|segment| segment.colour).collect();
+        let svg = svg::render(trench.vertices(), &colours);
+
+        assert_eq!(svg.matches("<line").count(), 14);
+        assert!(svg.contains(r##"x1="10.00" y1="10.00" x2="530.00" y2="10.00" stroke="#70c710""##));
+
+        let square = [(0, 0), (0, 1), (1, 1), (1, 0)];
+        let colours = ["#000000", "red\" onload=\"x", "#12345", "#abcdef"].map(str::to_string);
+        assert_eq!(svg::render(&square, &colours).matches("<line").count(), 2);
+    }
+
+    #[test]
+    fn test_malformed_lines() {
+        let parse = parse_data_part1;
+        assert!(parse("R 6 (#70c710)").is_ok());
+        assert_eq!(parse("R 6").unwrap_err(), "line 1: expected '<direction> <steps> (#rrggbb)', got 'R 6'");
+        assert!(parse("X 6 (#70c710)").is_err());
+        assert!(parse("R six (#70c710)").is_err());
+        assert!(parse("R 6 (#70c71g)").is_err());
+        assert!(parse("R 6 (red)").is_err());
+
+        assert!(parse_data_part2("R 6 (#70c714)").is_err());
+        assert!(parse_data_part2("R 6 (#70c7)").is_err());
+    }
+}
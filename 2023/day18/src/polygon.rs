@@ -0,0 +1,198 @@
+// Closed rectilinear polygons on the integer lattice, with vertices as (row, col).
+
+pub type Point = (i64, i64);
+
+#[derive(Debug)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+    closed: bool,
+}
+
+impl Polygon {
+    // Follows unit-direction moves from the origin, one vertex per move.
+    pub fn trace(moves: impl Iterator<Item = (Point, u64)>) -> Self {
+        let mut pos = (0i64, 0i64);
+        let mut vertices = vec![pos];
+        for ((d_row, d_col), steps) in moves {
+            pos = (pos.0 + d_row * steps as i64, pos.1 + d_col * steps as i64);
+            vertices.push(pos);
+        }
+        let closed = vertices.len() > 1 && vertices.first() == vertices.last();
+        if closed {
+            vertices.pop();
+        }
+        Self{vertices, closed}
+    }
+
+    // Whether the moves end where they started. Nothing closes an open path: area and
+    // boundary_points still measure it up to a straight edge back to the start, but the
+    // Pick's theorem counts refuse it.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Point, Point)> + '_ {
+        self.vertices.iter().cloned().zip(self.vertices.iter().cloned().cycle().skip(1))
+    }
+
+    // Shoelace formula, counted in lattice cells.
+    pub fn area(&self) -> u64 {
+        let twice_area: i64 = self.edges()
+            .map(|(a, b)| a.0 * b.1 - a.1 * b.0)
+            .sum();
+        twice_area.unsigned_abs() / 2
+    }
+
+    pub fn boundary_points(&self) -> u64 {
+        self.edges()
+            .map(|(a, b)| a.0.abs_diff(b.0) + a.1.abs_diff(b.1))
+            .sum()
+    }
+
+    // Pick's theorem: A = i + b/2 - 1. Only holds for a simple closed loop, so this is
+    // None for open paths and for loops that double back so far the count goes negative;
+    // other self-intersections go unnoticed here, see self_intersections.
+    pub fn interior_points(&self) -> Option<u64> {
+        if !self.closed {
+            return None;
+        }
+        (self.area() + 1).checked_sub(self.boundary_points() / 2)
+    }
+
+    pub fn lattice_points(&self) -> Option<u64> {
+        Some(self.interior_points()? + self.boundary_points())
+    }
+
+    pub fn bounding_box(&self) -> (Point, Point) {
+        let min_row = self.vertices.iter().map(|v| v.0).min().unwrap_or(0);
+        let max_row = self.vertices.iter().map(|v| v.0).max().unwrap_or(0);
+        let min_col = self.vertices.iter().map(|v| v.1).min().unwrap_or(0);
+        let max_col = self.vertices.iter().map(|v| v.1).max().unwrap_or(0);
+        ((min_row, min_col), (max_row, max_col))
+    }
+
+    // Pairs of edge indices that touch or cross, ignoring the shared corner of
+    // consecutive edges.
+    pub fn self_intersections(&self) -> Vec<(usize, usize)> {
+        let edges: Vec<(Point, Point)> = self.edges().collect();
+        let n = edges.len();
+        let mut intersections = Vec::new();
+
+        for i in 0..n {
+            for j in i + 1..n {
+                let adjacent = j == i + 1 || (i == 0 && j == n - 1);
+                let (a, b) = (edges[i], edges[j]);
+                let overlap = a.0.0.min(a.1.0) <= b.0.0.max(b.1.0) && b.0.0.min(b.1.0) <= a.0.0.max(a.1.0) &&
+                              a.0.1.min(a.1.1) <= b.0.1.max(b.1.1) && b.0.1.min(b.1.1) <= a.0.1.max(a.1.1);
+                if !overlap {
+                    continue;
+                }
+                // Consecutive edges always share their corner; they only intersect if
+                // the second one doubles back over the first.
+                if adjacent && !Self::is_collinear(a, b) {
+                    continue;
+                }
+                intersections.push((i, j));
+            }
+        }
+
+        intersections
+    }
+
+    fn is_collinear(a: (Point, Point), b: (Point, Point)) -> bool {
+        (a.0.0 == a.1.0 && b.0.0 == b.1.0 && a.0.0 == b.0.0) ||
+        (a.0.1 == a.1.1 && b.0.1 == b.1.1 && a.0.1 == b.0.1)
+    }
+
+    fn on_boundary(&self, point: Point) -> bool {
+        self.edges().any(|(a, b)| {
+            point.0 >= a.0.min(b.0) && point.0 <= a.0.max(b.0) &&
+            point.1 >= a.1.min(b.1) && point.1 <= a.1.max(b.1)
+        })
+    }
+
+    // Ray cast towards increasing columns, counting vertical edges over the half-open
+    // row span so that corners are not counted twice.
+    fn is_inside(&self, point: Point) -> bool {
+        self.edges()
+            .filter(|(a, b)| a.1 == b.1 && a.1 > point.1)
+            .filter(|(a, b)| point.0 >= a.0.min(b.0) && point.0 < a.0.max(b.0))
+            .count() % 2 == 1
+    }
+
+    // Renders dug cells as '#' and the rest as '.', or None if the bounding box holds
+    // more than max_cells cells.
+    pub fn rasterize(&self, max_cells: u64) -> Option<String> {
+        let ((min_row, min_col), (max_row, max_col)) = self.bounding_box();
+        let rows = max_row.abs_diff(min_row) + 1;
+        let cols = max_col.abs_diff(min_col) + 1;
+        if rows.checked_mul(cols)? > max_cells {
+            return None;
+        }
+
+        let mut grid = String::new();
+        for row in min_row..=max_row {
+            for col in min_col..=max_col {
+                let dug = self.on_boundary((row, col)) || self.is_inside((row, col));
+                grid.push(if dug { '#' } else { '.' });
+            }
+            grid.push('\n');
+        }
+
+        Some(grid)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    fn square(side: u64) -> Polygon {
+        Polygon::trace([((0, 1), side), ((1, 0), side), ((0, -1), side), ((-1, 0), side)].into_iter())
+    }
+
+    #[test]
+    fn test_square_measures() {
+        let polygon = square(2);
+
+        assert!(polygon.is_closed());
+        assert_eq!(polygon.area(), 4);
+        assert_eq!(polygon.boundary_points(), 8);
+        assert_eq!(polygon.interior_points(), Some(1));
+        assert_eq!(polygon.lattice_points(), Some(9));
+        assert_eq!(polygon.bounding_box(), ((0, 0), (2, 2)));
+        assert_eq!(polygon.self_intersections(), vec![]);
+        assert_eq!(polygon.rasterize(9), Some("###\n###\n###\n".to_string()));
+        assert_eq!(polygon.rasterize(8), None);
+    }
+
+    #[test]
+    fn test_self_intersections() {
+        // A figure eight: edge 3 crosses edge 0.
+        let polygon = Polygon::trace([((0, 1), 2), ((1, 0), 1), ((0, -1), 1), ((-1, 0), 2),
+                                      ((0, -1), 1), ((1, 0), 1)].into_iter());
+        assert_eq!(polygon.self_intersections(), vec![(0, 3)]);
+
+        // Doubling back over the first edge, then touching it again.
+        let polygon = Polygon::trace([((0, 1), 3), ((0, -1), 1), ((1, 0), 1), ((0, -1), 2),
+                                      ((-1, 0), 1)].into_iter());
+        assert_eq!(polygon.self_intersections(), vec![(0, 1), (0, 2)]);
+    }
+
+    #[test]
+    fn test_degenerate_paths() {
+        let there_and_back = Polygon::trace([((0, 1), 3), ((0, -1), 3)].into_iter());
+        assert!(there_and_back.is_closed());
+        assert_eq!(there_and_back.interior_points(), None);
+        assert_eq!(there_and_back.lattice_points(), None);
+
+        let open = Polygon::trace([((0, 1), 3), ((1, 0), 3)].into_iter());
+        assert!(!open.is_closed());
+        assert_eq!(open.interior_points(), None);
+    }
+}
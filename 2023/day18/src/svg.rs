@@ -0,0 +1,46 @@
+use std::fmt::Write;
+
+use crate::polygon::Point;
+
+const CANVAS: f64 = 800.0;
+const MARGIN: f64 = 10.0;
+
+// Whether a colour is written as #rrggbb, the only form allowed into the stroke attribute.
+pub fn is_hex_colour(colour: &str) -> bool {
+    colour.len() == 7 && colour.starts_with('#') && colour[1..].bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Draws each edge of the outline in its own colour, scaled so the longer side of the
+// bounding box spans the canvas. The edge from vertices[i] to vertices[i + 1] (wrapping
+// around) uses colours[i]; edges whose colour is not #rrggbb are left out.
+pub fn render(vertices: &[Point], colours: &[String]) -> String {
+    let min_row = vertices.iter().map(|v| v.0).min().unwrap_or(0);
+    let max_row = vertices.iter().map(|v| v.0).max().unwrap_or(0);
+    let min_col = vertices.iter().map(|v| v.1).min().unwrap_or(0);
+    let max_col = vertices.iter().map(|v| v.1).max().unwrap_or(0);
+
+    let extent = (max_row - min_row).max(max_col - min_col).max(1) as f64;
+    let scale = (CANVAS - 2.0 * MARGIN) / extent;
+    let width = (max_col - min_col) as f64 * scale + 2.0 * MARGIN;
+    let height = (max_row - min_row) as f64 * scale + 2.0 * MARGIN;
+    let project = |p: &Point| ((p.1 - min_col) as f64 * scale + MARGIN, (p.0 - min_row) as f64 * scale + MARGIN);
+
+    let mut svg = String::new();
+    writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {:.2} {:.2}">"#,
+             width, height, width, height).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    let ends = vertices.iter().cycle().skip(1);
+    for ((start, end), colour) in vertices.iter().zip(ends).zip(colours) {
+        if !is_hex_colour(colour) {
+            continue;
+        }
+        let (x1, y1) = project(start);
+        let (x2, y2) = project(end);
+        writeln!(svg, r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="{}" stroke-width="2" stroke-linecap="square"/>"#,
+                 x1, y1, x2, y2, colour).unwrap();
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
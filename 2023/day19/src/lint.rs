@@ -0,0 +1,200 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::{Graph, Output, Sieve};
+
+const ENTRY: &str = "in";
+const TERMINALS: [&str; 2] = ["A", "R"];
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum Issue<'a> {
+    MissingEntry,
+    UndefinedWorkflow { workflow: &'a str, rule: String },
+    UnreachableRule { workflow: &'a str, rule: String },
+    DeadWorkflow { workflow: &'a str },
+    Cycle { path: Vec<&'a str> },
+}
+
+impl fmt::Display for Issue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::MissingEntry =>
+                write!(f, "entry workflow '{}' is not defined", ENTRY),
+            Issue::UndefinedWorkflow { workflow, rule } =>
+                write!(f, "{}: rule '{}' refers to an undefined workflow", workflow, rule),
+            Issue::UnreachableRule { workflow, rule } =>
+                write!(f, "{}: rule '{}' can never fire", workflow, rule),
+            Issue::DeadWorkflow { workflow } =>
+                write!(f, "{}: workflow is never reached from '{}'", workflow, ENTRY),
+            Issue::Cycle { path } =>
+                write!(f, "cycle: {}", path.join(" -> ")),
+        }
+    }
+}
+
+fn rule_text(output: &Output) -> String {
+    match output {
+        (Some(rule), name) => format!("{}:{}", rule, name),
+        (None, name) => name.to_string(),
+    }
+}
+
+fn sorted_names<'a>(graph: &Graph<'a>) -> Vec<&'a str> {
+    let mut names: Vec<&str> = graph.keys()
+        .filter(|name| !TERMINALS.contains(name))
+        .copied()
+        .collect();
+    names.sort();
+    names
+}
+
+fn check_references<'a>(graph: &Graph<'a>, names: &[&'a str], issues: &mut Vec<Issue<'a>>) {
+    for &name in names {
+        for output in graph[name].outputs.iter() {
+            if !graph.contains_key(output.1) {
+                issues.push(Issue::UndefinedWorkflow { workflow: name, rule: rule_text(output) });
+            }
+        }
+    }
+}
+
+fn check_rules<'a>(graph: &Graph<'a>, names: &[&'a str], issues: &mut Vec<Issue<'a>>) {
+    for &name in names {
+        let mut sieve = Sieve::new();
+        for output in graph[name].outputs.iter() {
+            let fires = match output {
+                (Some(rule), _) => {
+                    let fires = !sieve.restrict(rule).is_empty();
+                    sieve = sieve.exclude(rule);
+                    fires
+                },
+                (None, _) => !sieve.is_empty(),
+            };
+            if !fires {
+                issues.push(Issue::UnreachableRule { workflow: name, rule: rule_text(output) });
+            }
+        }
+    }
+}
+
+fn check_dead<'a>(graph: &Graph<'a>, names: &[&'a str], issues: &mut Vec<Issue<'a>>) {
+    let mut visited: HashSet<&str> = HashSet::new();
+    let mut candidates: VecDeque<&str> = VecDeque::new();
+    if graph.contains_key(ENTRY) {
+        candidates.push_back(ENTRY);
+    }
+
+    while let Some(name) = candidates.pop_front() {
+        if !visited.insert(name) {
+            continue;
+        }
+        for output in graph[name].outputs.iter() {
+            if graph.contains_key(output.1) {
+                candidates.push_back(output.1);
+            }
+        }
+    }
+
+    for &name in names {
+        if !visited.contains(name) {
+            issues.push(Issue::DeadWorkflow { workflow: name });
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Mark {
+    Open,
+    Done,
+}
+
+fn visit<'a>(graph: &Graph<'a>, name: &'a str, marks: &mut HashMap<&'a str, Mark>,
+             path: &mut Vec<&'a str>, issues: &mut Vec<Issue<'a>>) {
+    marks.insert(name, Mark::Open);
+    path.push(name);
+
+    let mut targets: Vec<&str> = graph[name].outputs.iter()
+        .map(|output| output.1)
+        .filter(|target| graph.contains_key(target))
+        .collect();
+    targets.dedup();
+
+    for target in targets {
+        match marks.get(target) {
+            Some(Mark::Open) => {
+                let start = path.iter().position(|&n| n == target).unwrap();
+                let mut cycle = path[start..].to_vec();
+                cycle.push(target);
+                issues.push(Issue::Cycle { path: cycle });
+            },
+            Some(Mark::Done) => {},
+            None => visit(graph, target, marks, path, issues),
+        }
+    }
+
+    path.pop();
+    marks.insert(name, Mark::Done);
+}
+
+fn check_cycles<'a>(graph: &Graph<'a>, names: &[&'a str], issues: &mut Vec<Issue<'a>>) {
+    let mut marks: HashMap<&str, Mark> = HashMap::new();
+    for &name in names {
+        if !marks.contains_key(name) {
+            visit(graph, name, &mut marks, &mut Vec::new(), issues);
+        }
+    }
+}
+
+pub fn lint<'a>(graph: &Graph<'a>) -> Vec<Issue<'a>> {
+    let names = sorted_names(graph);
+    let mut issues = Vec::new();
+
+    if !graph.contains_key(ENTRY) {
+        issues.push(Issue::MissingEntry);
+    }
+    check_references(graph, &names, &mut issues);
+    check_rules(graph, &names, &mut issues);
+    check_dead(graph, &names, &mut issues);
+    check_cycles(graph, &names, &mut issues);
+
+    issues
+}
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+    use crate::build_graph;
+
+    #[test]
+    fn test_clean_workflows() {
+        let graph = build_graph("in{x<10:a,R}\na{m>5:A,R}");
+        assert_eq!(lint(&graph), vec![]);
+    }
+
+    #[test]
+    fn test_unreachable_rules() {
+        let graph = build_graph("in{x<10:A,x<5:R,x>9:A,R}");
+        assert_eq!(lint(&graph), vec![
+            Issue::UnreachableRule { workflow: "in", rule: "x<5:R".to_string() },
+            Issue::UnreachableRule { workflow: "in", rule: "R".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_undefined_and_dead_workflows() {
+        let graph = build_graph("in{x<10:foo,A}\nbar{A}");
+        assert_eq!(lint(&graph), vec![
+            Issue::UndefinedWorkflow { workflow: "in", rule: "x<10:foo".to_string() },
+            Issue::DeadWorkflow { workflow: "bar" },
+        ]);
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = build_graph("in{x<10:a,R}\na{m>5:b,A}\nb{a<3:in,R}");
+        assert_eq!(lint(&graph), vec![
+            Issue::Cycle { path: vec!["a", "b", "in", "a"] },
+        ]);
+    }
+}
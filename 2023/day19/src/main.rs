@@ -1,6 +1,10 @@
+use std::fmt;
+use std::process::ExitCode;
 use std::str::FromStr;
 use std::collections::{HashMap, HashSet, VecDeque};
 
+mod lint;
+
 #[derive(Debug)]
 struct Rule {
     field: char,
@@ -20,6 +24,12 @@ impl FromStr for Rule {
     }
 }
 
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", self.field, self.op, self.value)
+    }
+}
+
 type Output<'a> = (Option<Rule>, &'a str);
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -44,6 +54,10 @@ impl Sieve {
         Self{x, m, a, s}
     }
 
+    fn is_empty(&self) -> bool {
+        [self.x, self.m, self.a, self.s].iter().any(|&(min, max)| min + 1 >= max)
+    }
+
     fn restrict(&self, rule: &Rule) -> Self {
         let op = match rule.op {
             '<' => |(min, max), val| (min, u64::min(max, val)),
@@ -196,20 +210,54 @@ fn calc_part_2(graph: &Graph) -> u64 {
     combinations
 }
 
-fn process_input(input_file_path: &str) -> (u64, u64) {
-    let data = std::fs::read_to_string(input_file_path).unwrap();
-    let mut sections = data.split("\n\n");
+fn process_input(input_file_path: &str) -> Result<(u64, u64), Vec<String>> {
+    let data = std::fs::read_to_string(input_file_path)
+        .map_err(|e| vec![format!("cannot read '{}': {}", input_file_path, e)])?;
+    let (workflows, parts) = data.split_once("\n\n")
+        .ok_or(vec!["missing blank line between workflows and parts".to_string()])?;
+
+    let mut graph = build_graph(workflows);
+    let parts = get_parts(parts);
 
-    let mut graph = build_graph(sections.next().unwrap());
-    let parts = get_parts(sections.next().unwrap());
+    let issues = lint::lint(&graph);
+    if !issues.is_empty() {
+        return Err(issues.iter().map(|issue| issue.to_string()).collect());
+    }
 
     calc_sieves(&mut graph);
 
-    (calc_part_1(&graph, &parts), calc_part_2(&graph))
+    Ok((calc_part_1(&graph, &parts), calc_part_2(&graph)))
 }
 
-fn main() {
-    println!("{:?}", process_input("day19/input/test.txt"));
-    println!("{:?}", process_input("day19/input/input.txt"));
+fn main() -> ExitCode {
+    for input_file_path in ["day19/input/test.txt", "day19/input/input.txt"] {
+        match process_input(input_file_path) {
+            Ok(result) => println!("{:?}", result),
+            Err(issues) => {
+                for issue in issues {
+                    eprintln!("{}: {}", input_file_path, issue);
+                }
+                return ExitCode::from(1);
+            },
+        }
+    }
+    ExitCode::SUCCESS
 }
 
+
+#[cfg(test)]
+mod unit_tests {
+
+    use super::*;
+
+    #[test]
+    fn test_process_input() {
+        assert_eq!(process_input("input/test.txt"), Ok((19114, 167409079868000)));
+    }
+
+    #[test]
+    fn test_lint_rejects_input() {
+        assert_eq!(process_input("input/test_cycle.txt"), Err(vec!["cycle: a -> b -> in -> a".to_string()]));
+        assert!(process_input("input/missing.txt").unwrap_err()[0].starts_with("cannot read"));
+    }
+}
@@ -1,6 +1,6 @@
 use std::str::FromStr;
-use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::collections::HashSet;
+use std::process::ExitCode;
 
 #[derive(Debug)]
 struct Grid {
@@ -64,59 +64,110 @@ impl Grid {
     }
 }
 
-fn get_candidates(positions: HashSet<(i64, i64)>, grid: &Grid, wrap_around: bool) -> HashSet<(i64, i64)> {
-    let mut next = HashSet::new();
-    for pos in positions {
-        next.extend(grid.get_neighbors(pos, wrap_around));
-    }
-    next
+// Plots already reached by the BFS: a bitset over the square the BFS can cover, unless
+// that square would take more than 16 MiB, which is several times what the puzzle needs.
+enum Visited {
+    Dense { origin: (i64, i64), side: i64, bits: Vec<u64> },
+    Sparse(HashSet<(i64, i64)>),
 }
 
-fn calc_positions(grid: &Grid, steps: u64, wrap_around: bool) -> (u64, HashMap<(i64, i64), u64>) {
-    let mut positions = HashMap::<(i64, i64), u64>::new();
-    let mut candidates = HashSet::<(i64, i64)>::new();
+const MAX_DENSE_CELLS: u64 = 1 << 27;
 
-    let mut even_positions = 0u64;
-    let mut odd_positions = 0u64;
+impl Visited {
+    fn new(grid: &Grid, start: (i64, i64), radius: u64, wrap_around: bool) -> Self {
+        let (origin, side) = if wrap_around {
+            let reach = radius.min(MAX_DENSE_CELLS) as i64;
+            ((start.0 - reach, start.1 - reach), 2 * reach as u64 + 1)
+        } else {
+            ((0, 0), grid.rows.max(grid.cols) as u64)
+        };
+        if side * side <= MAX_DENSE_CELLS {
+            Visited::Dense{origin, side: side as i64, bits: vec![0; (side * side).div_ceil(64) as usize]}
+        } else {
+            Visited::Sparse(HashSet::new())
+        }
+    }
 
-    candidates.insert(grid.get_s_pos().unwrap());
+    fn insert(&mut self, pos: (i64, i64)) -> bool {
+        match self {
+            Visited::Dense{origin, side, bits} => {
+                let i = ((pos.0 - origin.0) * *side + pos.1 - origin.1) as usize;
+                let fresh = bits[i / 64] & (1 << (i % 64)) == 0;
+                bits[i / 64] |= 1 << (i % 64);
+                fresh
+            },
+            Visited::Sparse(set) => set.insert(pos),
+        }
+    }
+}
 
-    let mut duration = Instant::now();
+// Answers "how many plots are reachable in exactly N steps" for any N up to the radius
+// of a single frontier BFS. A plot first reached at step d can be revisited every second
+// step afterwards, so the answer is the number of plots with d <= N and d of N's parity.
+struct StepCounter {
+    // cumulative[d]: plots first reached at d or at an earlier step of the same parity.
+    cumulative: Vec<u64>,
+    // Whether the BFS ran out of plots before reaching its radius.
+    exhausted: bool,
+}
 
-    for iter in 1..=steps {
-        candidates = get_candidates(candidates, &grid, wrap_around)
-            .into_iter()
-            .filter(|c| !positions.contains_key(c))
-            .collect();
+impl StepCounter {
+    fn new(grid: &Grid, radius: u64, wrap_around: bool) -> Self {
+        let start = grid.get_s_pos().unwrap();
+        let mut visited = Visited::new(grid, start, radius, wrap_around);
+        visited.insert(start);
+        let mut frontier = vec![start];
+        let mut cumulative = vec![1u64];
+
+        for step in 1..=radius {
+            frontier = frontier.into_iter()
+                .flat_map(|pos| grid.get_neighbors(pos, wrap_around))
+                .filter(|&pos| visited.insert(pos))
+                .collect();
+            if frontier.is_empty() {
+                return Self{cumulative, exhausted: true};
+            }
 
-        if iter % 2 == 0 {
-            even_positions += candidates.len() as u64;
-        } else {
-            odd_positions += candidates.len() as u64;
+            let same_parity = if step >= 2 { cumulative[step as usize - 2] } else { 0 };
+            cumulative.push(same_parity + frontier.len() as u64);
         }
 
-        for candidate in candidates.iter() {
-            positions.entry(candidate.clone()).or_insert(iter);
-        }
+        Self{cumulative, exhausted: false}
+    }
+
+    fn radius(&self) -> u64 {
+        self.cumulative.len() as u64 - 1
+    }
 
-        if iter % 1000 == 0 {
-            println!("... Iter: {} Candidates: {} Duration: {:?} Avg: {:?}",
-                iter,
-                candidates.len(),
-                duration.elapsed(),
-                duration.elapsed() / candidates.len() as u32);
-            duration = Instant::now();
+    // None if steps lies beyond the BFS radius of a grid that was not fully explored.
+    fn reachable(&self, steps: u64) -> Option<u64> {
+        if steps <= self.radius() {
+            return Some(self.cumulative[steps as usize]);
+        }
+        if !self.exhausted {
+            return None;
+        }
+        // The last BFS step with the parity of steps; none if S is walled in and steps is odd.
+        match self.radius().checked_sub((self.radius() + steps) % 2) {
+            Some(last) => Some(self.cumulative[last as usize]),
+            None => Some(0),
         }
     }
 
-    match steps % 2 {
-        0 => (even_positions, positions),
-        1 => (odd_positions, positions),
-        _ => unreachable!(),
+    fn reachable_many(&self, steps: &[u64]) -> Vec<Option<u64>> {
+        steps.iter().map(|&n| self.reachable(n)).collect()
     }
 }
 
-fn solve_part_1() {
+fn read_grid(path: &str) -> Result<Grid, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read '{}': {}", path, e))?;
+    if data.lines().next().is_none_or(str::is_empty) {
+        return Err(format!("'{}' is empty", path));
+    }
+    Grid::from_str(data.as_str()).map_err(|_| format!("cannot parse '{}'", path))
+}
+
+fn solve_part_1() -> Result<(), String> {
     let cases = Vec::from([
         ("day21/input/test.txt", vec![
             (6u64, false),
@@ -135,101 +186,198 @@ fn solve_part_1() {
     ]);
 
     for (path, params) in cases {
-        let data = std::fs::read_to_string(path).unwrap();
-        let grid = Grid::from_str(data.as_str()).unwrap();
-        for &(steps, wrap_around) in params.iter() {
-            println!("Case: {:?}", (path, steps, wrap_around));
-            println!("... Result: {}", calc_positions(&grid, steps, wrap_around).0);
+        let grid = read_grid(path)?;
+        for wrap_around in [false, true] {
+            let steps: Vec<u64> = params.iter().filter(|p| p.1 == wrap_around).map(|p| p.0).collect();
+            let counter = StepCounter::new(&grid, steps.iter().copied().max().unwrap_or(0), wrap_around);
+            for (steps, result) in steps.iter().zip(counter.reachable_many(&steps)) {
+                println!("Case: {:?}", (path, steps, wrap_around));
+                println!("... Result: {}", result.unwrap());
+            }
         }
     }
+    Ok(())
 }
 
-fn get_positions(positions: &HashMap<(i64, i64), u64>, y_lim: (i64, i64), x_lim: (i64, i64)) -> (u64, u64) {
-    assert!(y_lim.0 <= y_lim.1 && x_lim.0 <= x_lim.1);
-    let mut num_odd = 0u64;
-    let mut num_even = 0u64;
-    for (&(y, x), &step) in positions {
-        let proper_y = y >= y_lim.0 && y < y_lim.1;
-        let proper_x = x >= x_lim.0 && x < x_lim.1;
-        match (proper_y, proper_x, step % 2) {
-            (true, true, 0) => num_even += 1,
-            (true, true, 1) => num_odd += 1,
-            _ => (),
-        }
+// Periods sampled before the quadratic fit is trusted, and extra periods it must predict.
+const MAX_WARMUP: u64 = 4;
+const VALIDATION: u64 = 2;
+
+// Beyond the first few grid widths, the number of plots reachable in offset + k * size
+// steps grows quadratically in k. Fits f(k0), f(k0 + 1), f(k0 + 2) for the smallest
+// warm-up k0 whose fit also predicts the next samples exactly, then extrapolates.
+// Returns None when no such fit is found within MAX_WARMUP periods.
+fn fit_quadratic(grid: &Grid, steps: u64) -> Option<u64> {
+    if grid.rows != grid.cols {
+        return None;
     }
+    let size = grid.rows as u64;
+    let offset = steps % size;
+    let periods = steps / size;
 
-    (num_even, num_odd)
-}
+    let last_sample = MAX_WARMUP + 2 + VALIDATION;
+    if periods <= last_sample {
+        return StepCounter::new(grid, steps, true).reachable(steps);
+    }
 
-fn solve_part_2() {
-    let path = "day21/input/input.txt";
-    let data = std::fs::read_to_string(&path).unwrap();
-    let grid = Grid::from_str(data.as_str()).unwrap();
-    let steps = 26501365u64;
-    let grid_size = 131u64;
+    let counter = StepCounter::new(grid, offset + last_sample * size, true);
+    let samples: Vec<i128> = (0..=last_sample)
+        .map(|k| counter.reachable(offset + k * size).unwrap() as i128)
+        .collect();
 
-    let mut positions = 0u64;
+    let predict = |k0: usize, t: i128| -> i128 {
+        let d1 = samples[k0 + 1] - samples[k0];
+        let d2 = samples[k0 + 2] - 2 * samples[k0 + 1] + samples[k0];
+        samples[k0] + t * d1 + t * (t - 1) / 2 * d2
+    };
 
-    assert!(grid.data.len() == grid_size as usize);
-    assert!(grid.data.len() == grid.data[0].len());
+    (0..=MAX_WARMUP as usize)
+        .find(|&k0| (3..3 + VALIDATION as usize).all(|t| predict(k0, t as i128) == samples[k0 + t]))
+        .map(|k0| predict(k0, periods as i128 - k0 as i128) as u64)
+}
 
+// Whether the row and column through S are free of rocks, which is what makes the
+// reachable area grow as a clean diamond of whole grids.
+fn has_clear_lanes(grid: &Grid) -> bool {
     let s_pos = grid.get_s_pos().unwrap();
     let s_row_empty = grid.data[s_pos.0 as usize].iter().all(|&c| c != '#');
     let s_col_empty = grid.data.iter().map(|v| v[s_pos.1 as usize]).all(|c| c != '#');
+    s_row_empty && s_col_empty
+}
+
+// Largest step count the simulation fallback takes on. The BFS covers a diamond of
+// about 2 * steps^2 plots, so far beyond this it would run for hours.
+const MAX_SIMULATED_STEPS: u64 = 5_000;
 
-    assert!(s_row_empty && s_col_empty);
+// How solve_infinite arrived at its count.
+#[derive(Debug, Eq, PartialEq)]
+enum Method {
+    Fit,
+    // Simulated every step, for the given reason.
+    Simulated(&'static str),
+}
+
+fn solve_infinite(grid: &Grid, steps: u64) -> Result<(u64, Method), String> {
+    if let Some(positions) = fit_quadratic(grid, steps) {
+        return Ok((positions, Method::Fit));
+    }
+
+    let reason = if has_clear_lanes(grid) {
+        "no stable quadratic fit"
+    } else {
+        "grid lacks clear lanes through S"
+    };
+    if steps > MAX_SIMULATED_STEPS {
+        return Err(format!("{} and {} steps is too many to simulate (at most {})", reason, steps, MAX_SIMULATED_STEPS));
+    }
+    let positions = StepCounter::new(grid, steps, true).reachable(steps).unwrap();
+    Ok((positions, Method::Simulated(reason)))
+}
+
+fn solve_part_2() -> Result<(), String> {
+    let cases = Vec::from([
+        ("day21/input/test.txt", vec![6u64, 10, 50, 100, 500, 1000, 5000]),
+        ("day21/input/input.txt", vec![26501365u64]),
+    ]);
+
+    for (path, params) in cases {
+        let grid = read_grid(path)?;
+        for &steps in params.iter() {
+            println!("Case: {:?}", (path, steps, true));
+            let (positions, method) = solve_infinite(&grid, steps)?;
+            if let Method::Simulated(reason) = method {
+                eprintln!("... {}, simulated {} steps", reason, steps);
+            }
+            println!("... Result: {}", positions);
+        }
+    }
+    Ok(())
+}
 
-    let grids_to_edge = (steps - grid_size / 2) / grid_size;
+fn main() -> ExitCode {
+    if let Err(e) = solve_part_1().and_then(|_| solve_part_2()) {
+        eprintln!("Error: {e}");
+        return ExitCode::from(1);
+    }
+    ExitCode::SUCCESS
+}
 
-    assert!(grids_to_edge == 202300);
+#[cfg(test)]
+mod unit_tests {
 
-    let full_grids_to_edge = grids_to_edge - 1;
+    use common::random::Lcg;
 
-    assert!(full_grids_to_edge % 2 == 1);
+    use super::*;
 
-    let full_odd_grids = full_grids_to_edge.pow(2);
-    let full_even_grids = (full_grids_to_edge + 1).pow(2);
+    fn test_grid() -> Grid {
+        Grid::from_str(&std::fs::read_to_string("input/test.txt").unwrap()).unwrap()
+    }
 
-    let edge_length = grids_to_edge + 1;
+    #[test]
+    fn test_infinite_garden() {
+        let grid = test_grid();
 
-    let (_, reference) = calc_positions(&grid, 65 + grid_size * 2, true);
+        assert!(!has_clear_lanes(&grid));
+        for (steps, expected) in [(6, 16), (10, 50), (50, 1594), (100, 6536), (500, 167004), (1000, 668697), (5000, 16733044)] {
+            assert_eq!(solve_infinite(&grid, steps).map(|(positions, _)| positions), Ok(expected), "{} steps", steps);
+        }
+        assert_eq!(solve_infinite(&grid, 500).map(|(_, method)| method), Ok(Method::Fit));
 
-    let tile = |pos:i64| {
-        let min = pos * grid_size as i64;
-        (min, min + grid_size as i64)
-    };
+        // The fit needs a square grid, so an open 3x5 garden is simulated: with nothing
+        // in the way, every plot of the right parity within the diamond is reachable.
+        let grid = Grid::from_str(".....\n..S..\n.....\n").unwrap();
+        assert_eq!(solve_infinite(&grid, 10), Ok((121, Method::Simulated("no stable quadratic fit"))));
+        assert!(solve_infinite(&grid, MAX_SIMULATED_STEPS + 1).is_err());
+    }
 
-    // Inside
-    positions += get_positions(&reference, tile(0), tile(0)).1 * full_odd_grids;
-    positions += get_positions(&reference, tile(1), tile(0)).1 * full_even_grids;
+    #[test]
+    fn test_fit_matches_simulation() {
+        // Sparse rocks with clear lanes through a centred S, like the real input.
+        let size = 21;
+        let mut lcg = Lcg::new(7);
+        let data: String = (0..size).map(|y| {
+            (0..size).map(|x| {
+                let rock = lcg.below(8) == 0;
+                match (y, x) {
+                    (10, 10) => 'S',
+                    (10, _) | (_, 10) => '.',
+                    _ if rock => '#',
+                    _ => '.',
+                }
+            }).collect::<String>() + "\n"
+        }).collect();
+        let grid = Grid::from_str(&data).unwrap();
+        let steps = 10 + 21 * 20;
 
-    // Corners
-    positions += get_positions(&reference, tile(2), tile(0)).1;
-    positions += get_positions(&reference, tile(-2), tile(0)).1;
-    positions += get_positions(&reference, tile(0), tile(2)).1;
-    positions += get_positions(&reference, tile(0), tile(-2)).1;
+        assert!(has_clear_lanes(&grid));
+        assert_eq!(fit_quadratic(&grid, steps), StepCounter::new(&grid, steps, true).reachable(steps));
+    }
 
-    // NE edge
-    positions += get_positions(&reference, tile(1), tile(1)).1 * (edge_length - 2);
-    positions += get_positions(&reference, tile(1), tile(2)).1 * (edge_length - 1);
+    #[test]
+    fn test_step_counter() {
+        let grid = test_grid();
 
-    // SE edge
-    positions += get_positions(&reference, tile(-1), tile(1)).1 * (edge_length - 2);
-    positions += get_positions(&reference, tile(-1), tile(2)).1 * (edge_length - 1);
+        let counter = StepCounter::new(&grid, 6, false);
+        assert_eq!(counter.reachable_many(&[0, 1, 2, 6]), vec![Some(1), Some(2), Some(4), Some(16)]);
 
-    // NW edge
-    positions += get_positions(&reference, tile(1), tile(-1)).1 * (edge_length - 2);
-    positions += get_positions(&reference, tile(1), tile(-2)).1 * (edge_length - 1);
+        let counter = StepCounter::new(&grid, 1000, false);
+        assert!(counter.exhausted);
+        assert_eq!(counter.reachable(1000), counter.reachable(counter.radius() - counter.radius() % 2));
+        assert_eq!(counter.reachable(1001), counter.reachable(counter.radius() - (counter.radius() + 1) % 2));
 
-    // SW edge
-    positions += get_positions(&reference, tile(-1), tile(-1)).1 * (edge_length - 2);
-    positions += get_positions(&reference, tile(-1), tile(-2)).1 * (edge_length - 1);
+        let counter = StepCounter::new(&grid, 100, true);
+        assert_eq!(counter.reachable_many(&[6, 10, 50, 100, 101]), vec![Some(16), Some(50), Some(1594), Some(6536), None]);
+    }
 
-    println!("Case: {:?}", (path, steps, true));
-    println!("... Result: {}", positions);
-}
+    #[test]
+    fn test_enclosed_start() {
+        let grid = Grid::from_str(".#.\n#S#\n.#.\n").unwrap();
 
-fn main() {
-    solve_part_1();
-    solve_part_2();
+        for wrap_around in [false, true] {
+            let counter = StepCounter::new(&grid, 10, wrap_around);
+            assert!(counter.exhausted);
+            assert_eq!(counter.radius(), 0);
+            assert_eq!(counter.reachable_many(&[0, 1, 2, 7, 10]), vec![Some(1), Some(0), Some(1), Some(0), Some(1)]);
+        }
+    }
 }